@@ -43,11 +43,12 @@ async fn main() -> miette::Result<()> {
     // Parse cli subcommands and arguments
     let cli = Cli::parse();
     // Get the settings for the run
-    let core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
+    let mut core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
     let config = Settings::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
     // Initialize the logger
     env_logger::Builder::from_env(Env::default().default_filter_or(&core.log))
         .init();
+    core.resolve_token().into_diagnostic()?;
     let client = default_client(None).into_diagnostic()?;
     // Match on the cli subcommand
     match &cli.command {
@@ -78,6 +79,7 @@ async fn main() -> miette::Result<()> {
                     let mut workspaces_variables =
                         variable::list_batch(&core, client, workspaces.clone())
                             .await
+                            .into_result()
                             .into_diagnostic()?;
                     // Filter the workspaces if query variables have been provided
                     if query.variables.is_some() {
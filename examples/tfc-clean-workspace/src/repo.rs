@@ -11,6 +11,7 @@ use url::Url;
 use walkdir::WalkDir;
 
 pub struct ProcessResults {
+    #[allow(dead_code)]
     pub repos: Vec<VcsRepo>,
     pub missing: Vec<VcsRepo>,
     pub failed: Vec<VcsRepo>,
@@ -27,8 +28,8 @@ fn build_path(config: &Settings, vcs: &VcsRepo, url: Url) -> String {
     let mut id = match vcs.identifier.clone() {
         Some(i) => i,
         None => {
-            let segments = url.path_segments().unwrap();
-            segments.last().unwrap().to_string()
+            let mut segments = url.path_segments().unwrap();
+            segments.next_back().unwrap().to_string()
         }
     };
     if let Some(branch) = &vcs.branch {
@@ -119,11 +119,12 @@ async fn main() -> miette::Result<()> {
     // Parse cli subcommands and arguments
     let cli = Cli::parse();
     // Get the settings for the run
-    let core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
+    let mut core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
     let config = Settings::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
     // Initialize the logger
     env_logger::Builder::from_env(Env::default().default_filter_or(&core.log))
         .init();
+    core.resolve_token().into_diagnostic()?;
     let max_concurrent = config
         .max_concurrent
         .unwrap_or(settings::MAX_CONCURRENT_DEFAULT.into());
@@ -169,6 +170,7 @@ async fn main() -> miette::Result<()> {
                     max_iterations,
                     status_check_sleep_seconds,
                     cancel_on_timeout,
+                    summarize_changes: false,
                 },
                 attributes,
                 client.clone(),
@@ -206,6 +208,7 @@ async fn main() -> miette::Result<()> {
                     max_iterations,
                     status_check_sleep_seconds,
                     cancel_on_timeout,
+                    summarize_changes: false,
                 },
                 attributes,
                 client.clone(),
@@ -16,6 +16,7 @@ pub type RunReport = Report<Meta, Data, Errors>;
 // For now need to keep this updated with best effort :)
 const REPORT_VERSION: &str = "0.1.0";
 
+#[allow(dead_code)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Variable {
     pub id: String,
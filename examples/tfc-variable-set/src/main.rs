@@ -111,10 +111,11 @@ async fn main() -> miette::Result<()> {
     // Parse cli subcommands and arguments
     let cli = Cli::parse();
     // Get the settings for the run
-    let core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
+    let mut core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
     // Initialize the logger
     env_logger::Builder::from_env(Env::default().default_filter_or(&core.log))
         .init();
+    core.resolve_token().into_diagnostic()?;
     let client = default_client(None).into_diagnostic()?;
 
     match &cli.command {
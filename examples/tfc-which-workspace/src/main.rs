@@ -9,13 +9,14 @@ use tfc_toolset_extras::default_client;
 #[async_std::main]
 async fn main() -> miette::Result<()> {
     // Get the settings for the run
-    let config = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
+    let mut config = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
 
     // Initialize the logger
     env_logger::Builder::from_env(
         Env::default().default_filter_or(&config.log),
     )
     .init();
+    config.resolve_token().into_diagnostic()?;
 
     let client = default_client(None).into_diagnostic()?;
 
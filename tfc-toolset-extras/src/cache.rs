@@ -0,0 +1,58 @@
+use crate::ExtrasError;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tfc_toolset::settings::Cache;
+
+/// A summary of the responses held in the HTTP cache
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CacheInfo {
+    pub dir: PathBuf,
+    pub entries: usize,
+    /// The combined size of the cached responses in bytes
+    pub size: u64,
+}
+
+/// The directory cached responses are stored in
+pub fn cache_dir(config: &Cache) -> PathBuf {
+    config.dir.clone().unwrap_or_else(|| {
+        dirs::cache_dir().unwrap_or("./".into()).join("tfc-toolset")
+    })
+}
+
+/// Count the entries in the HTTP cache and their combined size
+pub fn info(config: &Cache) -> Result<CacheInfo, ExtrasError> {
+    let dir = cache_dir(config);
+    let mut cache_info = CacheInfo { dir: dir.clone(), entries: 0, size: 0 };
+    // Nothing has been cached yet, or the cache has just been cleared
+    let is_empty = match dir.read_dir() {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => true,
+    };
+    if is_empty {
+        return Ok(cache_info);
+    }
+    for entry in cacache::list_sync(&dir) {
+        let entry = entry?;
+        cache_info.entries += 1;
+        cache_info.size += entry.size as u64;
+    }
+    Ok(cache_info)
+}
+
+/// Remove every entry from the HTTP cache
+pub async fn clear(config: &Cache) -> Result<(), ExtrasError> {
+    let dir = cache_dir(config);
+    if !dir.exists() {
+        info!(
+            "Cache directory {} does not exist, nothing to clear.",
+            dir.display()
+        );
+        return Ok(());
+    }
+    info!("Clearing cache at {}.", dir.display());
+    cacache::clear(&dir).await?;
+    info!("Successfully cleared cache!");
+    Ok(())
+}
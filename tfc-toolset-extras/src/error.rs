@@ -15,6 +15,15 @@ pub enum ExtrasError {
     /// Error parsing workspaces file
     #[error("Workspaces file is invalid. Each workspace entry must contain a name or ID.")]
     InvalidWorkspacesFile,
+    /// HCL related errors
+    #[error(transparent)]
+    Hcl(#[from] hcl::Error),
+    /// Dotenv related errors
+    #[error(transparent)]
+    Dotenv(#[from] dotenvy::Error),
+    /// HTTP cache related errors
+    #[error(transparent)]
+    Cache(#[from] cacache::Error),
     /// Error from core library
     #[error(transparent)]
     ToolError(#[from] tfc_toolset::error::ToolError),
@@ -0,0 +1,247 @@
+use crate::ExtrasError;
+use std::{
+    fmt::Write,
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+use tfc_toolset::{
+    error::ToolError,
+    variable::{Attributes, Category, Variable},
+};
+
+/// Environment variables read from a dotenv file
+#[derive(Clone, Debug, Default)]
+pub struct DotenvFile {
+    pub variables: Vec<Variable>,
+}
+
+impl From<Vec<Variable>> for DotenvFile {
+    fn from(variables: Vec<Variable>) -> Self {
+        let variables = variables
+            .into_iter()
+            .filter(|v| v.attributes.category == Category::Env)
+            .collect();
+        Self { variables }
+    }
+}
+
+// Single quoted values are taken literally, anything that can't be single
+// quoted is double quoted with escapes so it isn't expanded
+fn quote(value: &str) -> String {
+    if !value.contains('\'') && !value.contains('\n') {
+        return format!("'{}'", value);
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '$' => quoted.push_str("\\$"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl DotenvFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ExtrasError> {
+        let file = File::open(path).map_err(ToolError::Io)?;
+        Self::parse(file)
+    }
+
+    pub fn parse<R: Read>(reader: R) -> Result<Self, ExtrasError> {
+        let mut variables = Vec::new();
+        for item in dotenvy::from_read_iter(reader) {
+            let (key, value) = item?;
+            variables.push(Variable {
+                relationship_type: "vars".to_string(),
+                id: None,
+                attributes: Attributes {
+                    key,
+                    value: Some(value),
+                    description: None,
+                    category: Category::Env,
+                    hcl: None,
+                    sensitive: None,
+                },
+            });
+        }
+        Ok(Self { variables })
+    }
+
+    /// Render the variables as a dotenv file, sensitive variables have no
+    /// value and are written as comments
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for var in &self.variables {
+            let attributes = &var.attributes;
+            match &attributes.value {
+                Some(value) => {
+                    let _ =
+                        writeln!(output, "{}={}", attributes.key, quote(value));
+                }
+                None => {
+                    let _ = writeln!(
+                        output,
+                        "# {} is sensitive and must be set manually",
+                        attributes.key
+                    );
+                }
+            }
+        }
+        output
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ExtrasError> {
+        fs::write(path, self.render()).map_err(ToolError::Io)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(key: &str, value: Option<&str>) -> Variable {
+        Variable {
+            relationship_type: "vars".to_string(),
+            id: None,
+            attributes: Attributes {
+                key: key.to_string(),
+                value: value.map(str::to_string),
+                description: None,
+                category: Category::Env,
+                hcl: None,
+                sensitive: None,
+            },
+        }
+    }
+
+    fn values(file: &DotenvFile) -> Vec<(String, Option<String>)> {
+        file.variables
+            .iter()
+            .map(|v| (v.attributes.key.clone(), v.attributes.value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn quote_values() {
+        let cases = [
+            ("plain", "'plain'"),
+            ("", "''"),
+            ("a=b", "'a=b'"),
+            ("not # a comment", "'not # a comment'"),
+            ("$HOME", "'$HOME'"),
+            ("it's", r#""it's""#),
+            ("line one\nline two", r#""line one\nline two""#),
+            (r#"it's "quoted" \ $HOME"#, r#""it's \"quoted\" \\ \$HOME""#),
+        ];
+        for (value, quoted) in cases {
+            assert_eq!(quote(value), quoted, "quoting {:?}", value);
+        }
+    }
+
+    #[test]
+    fn parse_dotenv() {
+        let input = r#"
+# A comment
+AWS_REGION=us-east-1
+EMPTY=
+SINGLE='a=b # not a comment'
+DOUBLE="line one\nline two"
+export EXPORTED=yes
+INLINE=value # a comment
+"#;
+        let file = DotenvFile::parse(input.as_bytes()).unwrap();
+        assert_eq!(
+            values(&file),
+            vec![
+                ("AWS_REGION".into(), Some("us-east-1".into())),
+                ("EMPTY".into(), Some("".into())),
+                ("SINGLE".into(), Some("a=b # not a comment".into())),
+                ("DOUBLE".into(), Some("line one\nline two".into())),
+                ("EXPORTED".into(), Some("yes".into())),
+                ("INLINE".into(), Some("value".into())),
+            ]
+        );
+        assert!(file
+            .variables
+            .iter()
+            .all(|v| v.attributes.category == Category::Env));
+    }
+
+    #[test]
+    fn round_trip_values() {
+        let file = DotenvFile {
+            variables: vec![
+                var("PLAIN", Some("us-east-1")),
+                var("EMPTY", Some("")),
+                var("EQUALS", Some("key=value=more")),
+                var("HASH", Some("value # not a comment")),
+                var("LEADING_HASH", Some("#start")),
+                var("NEWLINES", Some("line one\nline two\n")),
+                var("SINGLE_QUOTE", Some("it's")),
+                var("DOUBLE_QUOTE", Some(r#"say "hi""#)),
+                var("MIXED", Some("it's \"all\" here\n# and = more")),
+                var("BACKSLASH", Some(r"C:\path\to\n")),
+                var("NO_EXPANSION", Some("$HOME and ${USER}")),
+                var("EXPANSION_QUOTED", Some("it's $HOME")),
+                var("SPACES", Some("  padded  ")),
+                var("UNICODE", Some("héllo ✓")),
+            ],
+        };
+        let parsed = DotenvFile::parse(file.render().as_bytes()).unwrap();
+        assert_eq!(values(&parsed), values(&file));
+    }
+
+    #[test]
+    fn render_sensitive_as_comment() {
+        let file = DotenvFile {
+            variables: vec![var("TOKEN", None), var("REGION", Some("eu"))],
+        };
+        let output = file.render();
+        assert_eq!(
+            output,
+            "# TOKEN is sensitive and must be set manually\nREGION='eu'\n"
+        );
+        let parsed = DotenvFile::parse(output.as_bytes()).unwrap();
+        assert_eq!(values(&parsed), vec![("REGION".into(), Some("eu".into()))]);
+    }
+
+    #[test]
+    fn only_env_variables_are_exported() {
+        let mut terraform = var("region", Some("us-east-1"));
+        terraform.attributes.category = Category::Terraform;
+        let file =
+            DotenvFile::from(vec![terraform, var("AWS_REGION", Some("eu"))]);
+        assert_eq!(
+            values(&file),
+            vec![("AWS_REGION".into(), Some("eu".into()))]
+        );
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("tfc-toolset-{}.env", std::process::id()));
+        let file = DotenvFile {
+            variables: vec![
+                var("REGION", Some("us-east-1")),
+                var("MESSAGE", Some("it's = # \"done\"\n")),
+            ],
+        };
+        file.save(&path).unwrap();
+        let loaded = DotenvFile::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(values(&loaded.unwrap()), values(&file));
+    }
+
+    #[test]
+    fn parse_invalid_dotenv() {
+        assert!(DotenvFile::parse("KEY='unterminated".as_bytes()).is_err());
+        assert!(DotenvFile::load("/does/not/exist.env").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod dotenv;
 pub mod tag;
+pub mod tfvars;
 pub mod variable;
 pub mod workspace;
@@ -0,0 +1,295 @@
+use crate::ExtrasError;
+use hcl::Expression;
+use std::{fmt::Write, fs, path::Path};
+use tfc_toolset::{
+    error::ToolError,
+    variable::{Attributes, Category, Variable},
+};
+
+/// Terraform variables read from a `.tfvars` file
+#[derive(Clone, Debug, Default)]
+pub struct TfvarsFile {
+    pub variables: Vec<Variable>,
+}
+
+// Scalars are stored as plain values, anything else is stored as HCL
+fn expression_value(expr: &Expression) -> Result<(String, bool), ExtrasError> {
+    match expr {
+        Expression::String(s) => Ok((s.clone(), false)),
+        Expression::Number(n) => Ok((n.to_string(), false)),
+        Expression::Bool(b) => Ok((b.to_string(), false)),
+        expr => Ok((hcl::format::to_string(expr)?, true)),
+    }
+}
+
+impl From<Vec<Variable>> for TfvarsFile {
+    fn from(variables: Vec<Variable>) -> Self {
+        let variables = variables
+            .into_iter()
+            .filter(|v| v.attributes.category == Category::Terraform)
+            .collect();
+        Self { variables }
+    }
+}
+
+impl TfvarsFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ExtrasError> {
+        let input = fs::read_to_string(path).map_err(ToolError::Io)?;
+        Self::parse(&input)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ExtrasError> {
+        let body = hcl::parse(input)?;
+        let mut variables = Vec::new();
+        for attribute in body.attributes() {
+            // A null value means the variable should use its default
+            if let Expression::Null = attribute.expr() {
+                continue;
+            }
+            let (value, hcl) = expression_value(attribute.expr())?;
+            variables.push(Variable {
+                relationship_type: "vars".to_string(),
+                id: None,
+                attributes: Attributes {
+                    key: attribute.key().to_string(),
+                    value: Some(value),
+                    description: None,
+                    category: Category::Terraform,
+                    hcl: Some(hcl),
+                    sensitive: None,
+                },
+            });
+        }
+        Ok(Self { variables })
+    }
+
+    /// Render the variables as a `.tfvars` file, sensitive variables have no
+    /// value and are written as comments
+    pub fn render(&self) -> Result<String, ExtrasError> {
+        let mut output = String::new();
+        for var in &self.variables {
+            let attributes = &var.attributes;
+            let value = match &attributes.value {
+                Some(value) if attributes.hcl.unwrap_or(false) => value.clone(),
+                Some(value) => {
+                    hcl::format::to_string(&Expression::String(value.clone()))?
+                }
+                None => {
+                    let _ = writeln!(
+                        output,
+                        "# {} is sensitive and must be set manually",
+                        attributes.key
+                    );
+                    continue;
+                }
+            };
+            let _ = writeln!(output, "{} = {}", attributes.key, value);
+        }
+        Ok(output)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ExtrasError> {
+        fs::write(path, self.render()?).map_err(ToolError::Io)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(key: &str, value: Option<&str>, hcl: bool) -> Variable {
+        Variable {
+            relationship_type: "vars".to_string(),
+            id: None,
+            attributes: Attributes {
+                key: key.to_string(),
+                value: value.map(str::to_string),
+                description: None,
+                category: Category::Terraform,
+                hcl: Some(hcl),
+                sensitive: None,
+            },
+        }
+    }
+
+    fn values(file: &TfvarsFile) -> Vec<(String, Option<String>, bool)> {
+        file.variables
+            .iter()
+            .map(|v| {
+                let a = &v.attributes;
+                (a.key.clone(), a.value.clone(), a.hcl.unwrap_or(false))
+            })
+            .collect()
+    }
+
+    // HCL values may be reformatted, so compare the expressions they parse to
+    fn expr(value: &str) -> Expression {
+        let body = hcl::parse(&format!("v = {}", value)).unwrap();
+        body.attributes().next().unwrap().expr().clone()
+    }
+
+    fn assert_same(before: &TfvarsFile, after: &TfvarsFile) {
+        assert_eq!(before.variables.len(), after.variables.len());
+        for (before, after) in before.variables.iter().zip(&after.variables) {
+            let (before, after) = (&before.attributes, &after.attributes);
+            assert_eq!(before.key, after.key);
+            assert_eq!(before.hcl, after.hcl);
+            if before.hcl == Some(true) {
+                assert_eq!(
+                    expr(before.value.as_ref().unwrap()),
+                    expr(after.value.as_ref().unwrap())
+                );
+            } else {
+                assert_eq!(before.value, after.value);
+            }
+        }
+    }
+
+    fn round_trip(file: &TfvarsFile) -> TfvarsFile {
+        TfvarsFile::parse(&file.render().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_scalars() {
+        let file = TfvarsFile::parse(
+            r#"
+region = "us-east-1"
+instance_count = 3
+ratio = 0.5
+enabled = true
+unset = null
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            values(&file),
+            vec![
+                ("region".into(), Some("us-east-1".into()), false),
+                ("instance_count".into(), Some("3".into()), false),
+                ("ratio".into(), Some("0.5".into()), false),
+                ("enabled".into(), Some("true".into()), false),
+            ]
+        );
+        assert!(file
+            .variables
+            .iter()
+            .all(|v| v.attributes.category == Category::Terraform));
+    }
+
+    #[test]
+    fn parse_collections_as_hcl() {
+        let file = TfvarsFile::parse(
+            r#"
+zones = ["a", "b"]
+tags = {
+  team = "infra"
+  env  = "prod"
+}
+"#,
+        )
+        .unwrap();
+        let parsed = values(&file);
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.iter().all(|(_, _, hcl)| *hcl));
+        assert_eq!(
+            expr(parsed[0].1.as_ref().unwrap()),
+            Expression::Array(vec![
+                Expression::String("a".into()),
+                Expression::String("b".into())
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_heredoc() {
+        let file = TfvarsFile::parse(
+            "policy = <<EOT\n{\n  \"Version\": \"2012-10-17\"\n}\nEOT\n",
+        )
+        .unwrap();
+        assert_eq!(file.variables.len(), 1);
+        assert_eq!(file.variables[0].attributes.hcl, Some(true));
+        let value = file.variables[0].attributes.value.clone().unwrap();
+        let rendered = hcl::eval::Evaluate::evaluate(
+            &expr(&value),
+            &hcl::eval::Context::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            hcl::Value::String("{\n  \"Version\": \"2012-10-17\"\n}\n".into())
+        );
+        assert_same(&file, &round_trip(&file));
+    }
+
+    #[test]
+    fn round_trip_values() {
+        let file = TfvarsFile {
+            variables: vec![
+                var("region", Some("us-east-1"), false),
+                var("quoted", Some(r#"say "hi" \ bye"#), false),
+                var("template", Some("literal ${not_interpolated}"), false),
+                var("multiline", Some("line one\nline two\n"), false),
+                var("unicode", Some("héllo ✓"), false),
+                var("zones", Some(r#"["a", "b"]"#), true),
+                var("tags", Some(r#"{ team = "infra", env = "prod" }"#), true),
+                var(
+                    "nested",
+                    Some(r#"{ subnets = ["10.0.0.0/24"], public = false }"#),
+                    true,
+                ),
+            ],
+        };
+        assert_same(&file, &round_trip(&file));
+    }
+
+    #[test]
+    fn render_sensitive_as_comment() {
+        let file = TfvarsFile {
+            variables: vec![
+                var("password", None, false),
+                var("region", Some("us-east-1"), false),
+            ],
+        };
+        let output = file.render().unwrap();
+        assert_eq!(
+            output,
+            "# password is sensitive and must be set manually\nregion = \"us-east-1\"\n"
+        );
+        assert_eq!(values(&TfvarsFile::parse(&output).unwrap()).len(), 1);
+    }
+
+    #[test]
+    fn only_terraform_variables_are_exported() {
+        let mut env = var("AWS_REGION", Some("us-east-1"), false);
+        env.attributes.category = Category::Env;
+        let file = TfvarsFile::from(vec![
+            env,
+            var("region", Some("us-east-1"), false),
+        ]);
+        assert_eq!(file.variables.len(), 1);
+        assert_eq!(file.variables[0].attributes.key, "region");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("tfc-toolset-{}.tfvars", std::process::id()));
+        let file = TfvarsFile {
+            variables: vec![
+                var("region", Some("us-east-1"), false),
+                var("zones", Some(r#"["a", "b"]"#), true),
+            ],
+        };
+        file.save(&path).unwrap();
+        let loaded = TfvarsFile::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_same(&file, &loaded.unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_hcl() {
+        assert!(TfvarsFile::parse("region = ").is_err());
+        assert!(TfvarsFile::load("/does/not/exist.tfvars").is_err());
+    }
+}
@@ -1,10 +1,15 @@
+pub mod cache;
 pub mod error;
 pub mod file;
+pub mod rate_limit;
 
+pub use cache::{cache_dir, CacheInfo};
 pub use error::ExtrasError;
 pub use file::{
-    tag::TagsFile, variable::VariablesFile, workspace::WorkspacesFile,
+    dotenv::DotenvFile, tag::TagsFile, tfvars::TfvarsFile,
+    variable::VariablesFile, workspace::WorkspacesFile,
 };
+pub use rate_limit::{RateLimitMiddleware, RequestStats};
 use std::path::PathBuf;
 
 use http_cache_surf::{
@@ -14,8 +19,12 @@ use regex::Regex;
 use surf::Client;
 use surf_governor::GovernorMiddleware;
 use surf_retry::{ExponentialBackoff, RetryMiddleware};
-use tfc_toolset::error::ToolError;
+use tfc_toolset::{
+    error::ToolError,
+    settings::{self, Core, RateLimit},
+};
 
+#[deprecated(note = "use `build_rate_limit` instead")]
 pub fn build_governor() -> Result<GovernorMiddleware, ToolError> {
     match GovernorMiddleware::per_second(30) {
         Ok(g) => Ok(g),
@@ -23,6 +32,7 @@ pub fn build_governor() -> Result<GovernorMiddleware, ToolError> {
     }
 }
 
+#[deprecated(note = "use `build_rate_limit` instead")]
 pub fn build_retry() -> RetryMiddleware<ExponentialBackoff> {
     RetryMiddleware::new(
         99,
@@ -31,11 +41,20 @@ pub fn build_retry() -> RetryMiddleware<ExponentialBackoff> {
     )
 }
 
+pub fn build_rate_limit(config: &RateLimit) -> RateLimitMiddleware {
+    RateLimitMiddleware::new(config)
+}
+
 pub fn build_cache_options() -> HttpCacheOptions {
+    build_cache_options_with(&settings::Cache::default())
+}
+
+/// Build the cache options from the cache section of the config
+pub fn build_cache_options_with(config: &settings::Cache) -> HttpCacheOptions {
     HttpCacheOptions {
         cache_options: Some(CacheOptions {
             shared: false,
-            cache_heuristic: 0.0,
+            cache_heuristic: config.ttl_heuristic,
             immutable_min_time_to_live: Default::default(),
             ignore_cargo_cult: false,
         }),
@@ -45,19 +64,45 @@ pub fn build_cache_options() -> HttpCacheOptions {
     }
 }
 
+fn build_cache_mode(mode: &settings::CacheMode) -> CacheMode {
+    match mode {
+        settings::CacheMode::Default => CacheMode::Default,
+        settings::CacheMode::NoStore => CacheMode::NoStore,
+        settings::CacheMode::Reload => CacheMode::Reload,
+        settings::CacheMode::NoCache => CacheMode::NoCache,
+        settings::CacheMode::ForceCache => CacheMode::ForceCache,
+        settings::CacheMode::OnlyIfCached => CacheMode::OnlyIfCached,
+    }
+}
+
+pub fn build_cache(config: &settings::Cache) -> Cache<CACacheManager> {
+    Cache(HttpCache {
+        mode: build_cache_mode(&config.mode),
+        manager: CACacheManager { path: cache_dir(config) },
+        options: build_cache_options_with(config),
+    })
+}
+
 pub fn default_client(path: Option<PathBuf>) -> Result<Client, ToolError> {
-    // Build the http client with a cache, governor, and retry enabled
-    Ok(Client::new().with(build_retry()).with(build_governor()?).with(Cache(
-        HttpCache {
-            mode: CacheMode::Default,
-            manager: CACacheManager {
-                path: path.unwrap_or_else(|| {
-                    dirs::cache_dir().unwrap_or("./".into()).join("tfc-toolset")
-                }),
-            },
-            options: build_cache_options(),
-        },
-    )))
+    // Build the http client with a cache and the default rate limits
+    let cache = settings::Cache { dir: path, ..Default::default() };
+    Ok(Client::new()
+        .with(build_cache(&cache))
+        .with(build_rate_limit(&RateLimit::default())))
+}
+
+/// Build the http client with a cache and the rate limits from the config,
+/// returning a handle to the request counters along with it
+pub fn build_client(
+    config: &Core,
+) -> Result<(Client, RequestStats), ToolError> {
+    let rate_limit = build_rate_limit(&config.rate_limit);
+    let stats = rate_limit.stats();
+    // Cached responses are served before the rate limit is applied so they
+    // are neither delayed nor counted
+    let client =
+        Client::new().with(build_cache(&config.cache)).with(rate_limit);
+    Ok((client, stats))
 }
 
 pub fn parse_workspace_name(
@@ -0,0 +1,324 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use async_std::task;
+use log::{info, warn};
+use surf::{
+    http::{other::RetryAfter, StatusCode},
+    middleware::{Middleware, Next},
+    Client, Request, Response, Result,
+};
+use tfc_toolset::settings::RateLimit;
+
+const RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+const RATE_LIMIT_RESET: &str = "X-RateLimit-Reset";
+
+// Status codes that indicate the request may succeed if sent again as is
+const RETRY_CODES: [StatusCode; 6] = [
+    StatusCode::TooManyRequests,
+    StatusCode::RequestTimeout,
+    StatusCode::InternalServerError,
+    StatusCode::BadGateway,
+    StatusCode::ServiceUnavailable,
+    StatusCode::GatewayTimeout,
+];
+
+// The longest to back off for when the API gives no hint of its own
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Counters for the requests sent through a `RateLimitMiddleware`
+///
+/// Clones share the same counters, so a handle can be kept while the
+/// middleware is moved into the client.
+#[derive(Clone, Debug, Default)]
+pub struct RequestStats {
+    requests: Arc<AtomicU64>,
+    retries: Arc<AtomicU64>,
+    throttled_micros: Arc<AtomicU64>,
+}
+
+impl RequestStats {
+    /// The number of requests sent, including retries
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// The number of requests that were sent again after being throttled or
+    /// failing
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// The total time requests spent waiting before being sent
+    pub fn throttled(&self) -> Duration {
+        Duration::from_micros(self.throttled_micros.load(Ordering::Relaxed))
+    }
+
+    fn add_throttled(&self, wait: Duration) {
+        self.throttled_micros
+            .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Display for RequestStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Requests sent: {}, retries: {}, time throttled: {:.2}s",
+            self.requests(),
+            self.retries(),
+            self.throttled().as_secs_f64()
+        )
+    }
+}
+
+/// Middleware that paces requests and retries them when throttled or failed
+///
+/// Requests are spaced out to stay under `requests_per_second`. When the API
+/// reports through `X-RateLimit-Remaining` that the limit has been used up,
+/// every request waits for `X-RateLimit-Reset` before being sent. Requests
+/// that are throttled, time out, get a server error or fail to be sent at all
+/// are retried up to `max_retries` times, waiting for `Retry-After` if given,
+/// then `X-RateLimit-Reset`, then falling back to an exponential backoff.
+#[derive(Clone, Debug)]
+pub struct RateLimitMiddleware {
+    interval: Duration,
+    max_retries: u32,
+    // The earliest time the next request may be sent
+    next_at: Arc<Mutex<Instant>>,
+    stats: RequestStats,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(config: &RateLimit) -> Self {
+        Self {
+            interval: Duration::from_secs(1)
+                / config.requests_per_second.max(1),
+            max_retries: config.max_retries,
+            next_at: Arc::new(Mutex::new(Instant::now())),
+            stats: RequestStats::default(),
+        }
+    }
+
+    /// A handle to the counters for requests sent through this middleware
+    pub fn stats(&self) -> RequestStats {
+        self.stats.clone()
+    }
+
+    // Reserve the next slot to send a request in and wait for it
+    async fn wait_turn(&self) {
+        let wait = {
+            let mut next_at =
+                self.next_at.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next_at).max(now);
+            *next_at = slot + self.interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            self.stats.add_throttled(wait);
+            task::sleep(wait).await;
+        }
+    }
+
+    // Hold off all requests until the wait has passed
+    fn pause(&self, wait: Duration) {
+        let mut next_at =
+            self.next_at.lock().unwrap_or_else(|e| e.into_inner());
+        let until = Instant::now() + wait;
+        if until > *next_at {
+            *next_at = until;
+        }
+    }
+}
+
+fn header_secs(res: &Response, name: &str) -> Option<f64> {
+    res.header(name)?.last().as_str().trim().parse::<f64>().ok()
+}
+
+// How long until the rate limit resets, if the API says it has been used up
+fn rate_limit_reset(res: &Response) -> Option<Duration> {
+    let remaining = header_secs(res, RATE_LIMIT_REMAINING)?;
+    let reset = header_secs(res, RATE_LIMIT_RESET)?;
+    if remaining > 0.0 || !reset.is_finite() || reset < 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(reset))
+}
+
+fn retry_after(res: &Response) -> Option<Duration> {
+    let retry_after = RetryAfter::from_headers(res).ok()??;
+    Some(retry_after.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1)
+        .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .unwrap_or(MAX_BACKOFF)
+        .min(MAX_BACKOFF)
+}
+
+#[surf::utils::async_trait]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        client: Client,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        // The body can only be read once, so keep a copy to send on retries
+        let body = req.take_body().into_bytes().await?;
+        let mut attempt = 0;
+        loop {
+            let mut r = req.clone();
+            r.set_body(body.clone());
+            self.wait_turn().await;
+            self.stats.requests.fetch_add(1, Ordering::Relaxed);
+            let res = match next.run(r, client.clone()).await {
+                Ok(res) => res,
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    let wait = backoff(attempt);
+                    warn!(
+                        "Request to {} failed: {}, retrying in {:.2}s ({} of {})",
+                        req.url(),
+                        e,
+                        wait.as_secs_f64(),
+                        attempt,
+                        self.max_retries
+                    );
+                    self.stats.retries.fetch_add(1, Ordering::Relaxed);
+                    self.pause(wait);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let reset = rate_limit_reset(&res);
+            if let Some(reset) = reset {
+                info!(
+                    "Rate limit reached, pausing requests for {:.2}s",
+                    reset.as_secs_f64()
+                );
+                self.pause(reset);
+            }
+            if !RETRY_CODES.contains(&res.status())
+                || attempt >= self.max_retries
+            {
+                return Ok(res);
+            }
+            attempt += 1;
+            let wait =
+                retry_after(&res).or(reset).unwrap_or_else(|| backoff(attempt));
+            warn!(
+                "Request to {} returned {}, retrying in {:.2}s ({} of {})",
+                req.url(),
+                res.status(),
+                wait.as_secs_f64(),
+                attempt,
+                self.max_retries
+            );
+            self.stats.retries.fetch_add(1, Ordering::Relaxed);
+            self.pause(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::{
+        io::{ReadExt, WriteExt},
+        net::TcpListener,
+    };
+
+    fn client(max_retries: u32) -> (Client, RequestStats) {
+        let middleware = RateLimitMiddleware::new(&RateLimit {
+            requests_per_second: 100,
+            max_retries,
+        });
+        let stats = middleware.stats();
+        (Client::new().with(middleware), stats)
+    }
+
+    // Serve each of the statuses in turn, one per connection
+    async fn serve(statuses: Vec<u16>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        task::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 4096];
+                let _ = stream.read(&mut buf).await.unwrap();
+                let res = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(res.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(7), MAX_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        task::block_on(async {
+            let url = serve(vec![500, 200]).await;
+            let (client, stats) = client(3);
+            let res = client.get(url).await.unwrap();
+            assert_eq!(res.status(), StatusCode::Ok);
+            assert_eq!(stats.requests(), 2);
+            assert_eq!(stats.retries(), 1);
+        });
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        task::block_on(async {
+            let url = serve(vec![502, 504]).await;
+            let (client, stats) = client(1);
+            let res = client.get(url).await.unwrap();
+            assert_eq!(res.status(), StatusCode::GatewayTimeout);
+            assert_eq!(stats.retries(), 1);
+        });
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        task::block_on(async {
+            let url = serve(vec![404]).await;
+            let (client, stats) = client(3);
+            let res = client.get(url).await.unwrap();
+            assert_eq!(res.status(), StatusCode::NotFound);
+            assert_eq!(stats.retries(), 0);
+        });
+    }
+
+    #[test]
+    fn retries_transport_errors() {
+        task::block_on(async {
+            // Nothing is listening once the listener has been dropped
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            drop(listener);
+            let (client, stats) = client(1);
+            assert!(client.get(url).await.is_err());
+            assert_eq!(stats.requests(), 2);
+            assert_eq!(stats.retries(), 1);
+        });
+    }
+}
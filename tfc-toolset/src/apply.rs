@@ -0,0 +1,114 @@
+use crate::{
+    build_request,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    logs,
+    run::{self, Status},
+    settings::Core,
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use surf::{http::Method, Client};
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Attributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_additions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_changes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_destructions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_imports: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_read_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Apply {
+    pub id: String,
+    pub attributes: Attributes,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ApplyOuter {
+    pub data: Apply,
+}
+
+pub async fn show(
+    apply_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Apply, ToolError> {
+    info!("Retrieving apply {}.", apply_id);
+    let url = Url::parse(&format!("{}/applies/{}", config.base_url, apply_id))?;
+    let req = build_request(Method::Get, url, config, None);
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        info!("Successfully retrieved apply!");
+        let apply: ApplyOuter =
+            res.body_json().await.map_err(surf_to_tool_error)?;
+        Ok(apply.data)
+    } else {
+        error!("Failed to retrieve apply :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+pub async fn show_by_run(
+    run_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Apply, ToolError> {
+    let run = run::status(run_id, config, client.clone()).await?;
+    match run.apply_id() {
+        Some(apply_id) => show(&apply_id, config, client).await,
+        None => Err(ToolError::General(anyhow::anyhow!(
+            "Run {} does not have an apply",
+            run_id
+        ))),
+    }
+}
+
+/// Retrieve the complete log for an apply
+pub async fn logs(
+    apply_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<String, ToolError> {
+    let apply = show(apply_id, config, client.clone()).await?;
+    match apply.attributes.log_read_url {
+        Some(log_read_url) => logs::read_all(&log_read_url, client).await,
+        None => Ok(String::new()),
+    }
+}
+
+/// Follow the log for an apply until it has finished
+pub async fn follow_logs<C: FnMut(&str)>(
+    apply_id: &str,
+    poll_interval: Duration,
+    config: &Core,
+    client: Client,
+    on_chunk: C,
+) -> Result<(), ToolError> {
+    logs::follow(
+        || {
+            let client = client.clone();
+            async move {
+                let apply = show(apply_id, config, client).await?;
+                Ok((
+                    apply.attributes.log_read_url,
+                    apply.attributes.status.unwrap_or_default(),
+                ))
+            }
+        },
+        poll_interval,
+        client.clone(),
+        on_chunk,
+    )
+    .await
+}
@@ -0,0 +1,658 @@
+use crate::{
+    apply::{self, Apply},
+    error::ToolError,
+    pagination::Paginator,
+    plan::{self, ChangeSummary, Plan, PlanJson},
+    run::{self, Attributes as RunAttributes, QueueOptions, QueueResult, Run},
+    settings::Core,
+    state_version::{self, Output, StateVersion},
+    tag::{self, Tags},
+    variable::{
+        self, Attributes as VariableAttributes, Variable, VariablesBatch,
+    },
+    variable_set::{self, VarSet, VarSetOptions, VarSets},
+    workspace::{self, Attributes as WorkspaceAttributes, Workspace},
+};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use surf::{middleware::Middleware, Client};
+use url::Url;
+
+/// A handle bundling the http client and settings needed to talk to the API
+#[derive(Clone, Debug)]
+pub struct TfcClient {
+    pub config: Core,
+    pub client: Client,
+}
+
+impl TfcClient {
+    pub fn new(config: Core, client: Client) -> Self {
+        Self { config, client }
+    }
+
+    /// Add a middleware to the underlying http client
+    pub fn with(mut self, middleware: impl Middleware) -> Self {
+        self.client = self.client.with(middleware);
+        self
+    }
+
+    /// Override the base URL used when building requests
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.config.set_base_url(base_url);
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.config.base_url
+    }
+
+    /// Build a paginator for any list endpoint, relative to the base URL
+    pub fn paginate<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Paginator<T>, ToolError> {
+        let url = Url::parse(&format!("{}/{}", self.config.base_url, path))?;
+        Paginator::new(url, &self.config, self.client.clone())
+    }
+
+    pub fn workspaces(&self) -> WorkspaceApi<'_> {
+        WorkspaceApi { tfc: self }
+    }
+
+    pub fn runs(&self) -> RunApi<'_> {
+        RunApi { tfc: self }
+    }
+
+    pub fn plans(&self) -> PlanApi<'_> {
+        PlanApi { tfc: self }
+    }
+
+    pub fn applies(&self) -> ApplyApi<'_> {
+        ApplyApi { tfc: self }
+    }
+
+    pub fn state_versions(&self) -> StateVersionApi<'_> {
+        StateVersionApi { tfc: self }
+    }
+
+    pub fn variables(&self) -> VariableApi<'_> {
+        VariableApi { tfc: self }
+    }
+
+    pub fn tags(&self) -> TagApi<'_> {
+        TagApi { tfc: self }
+    }
+
+    pub fn variable_sets(&self) -> VariableSetApi<'_> {
+        VariableSetApi { tfc: self }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WorkspaceApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl WorkspaceApi<'_> {
+    pub async fn show(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Workspace, ToolError> {
+        workspace::show(workspace_id, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn show_by_name(
+        &self,
+        workspace_name: &str,
+    ) -> Result<Workspace, ToolError> {
+        workspace::show_by_name(
+            workspace_name,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn list(
+        &self,
+        filter: bool,
+    ) -> Result<Vec<Workspace>, ToolError> {
+        workspace::list(filter, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn create(
+        &self,
+        options: WorkspaceAttributes,
+    ) -> Result<Workspace, ToolError> {
+        workspace::create(options, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn update(
+        &self,
+        workspace_id: &str,
+        options: WorkspaceAttributes,
+    ) -> Result<Workspace, ToolError> {
+        workspace::update(
+            workspace_id,
+            options,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn update_by_name(
+        &self,
+        workspace_name: &str,
+        options: WorkspaceAttributes,
+    ) -> Result<Workspace, ToolError> {
+        workspace::update_by_name(
+            workspace_name,
+            options,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn delete(
+        &self,
+        workspace_id: &str,
+        safe_delete: bool,
+    ) -> Result<(), ToolError> {
+        workspace::delete(
+            workspace_id,
+            safe_delete,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn delete_by_name(
+        &self,
+        workspace_name: &str,
+        safe_delete: bool,
+    ) -> Result<(), ToolError> {
+        workspace::delete_by_name(
+            workspace_name,
+            safe_delete,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn lock(
+        &self,
+        workspace_id: &str,
+        reason: Option<String>,
+    ) -> Result<Workspace, ToolError> {
+        workspace::lock(
+            workspace_id,
+            reason,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn unlock(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Workspace, ToolError> {
+        workspace::unlock(
+            workspace_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn force_unlock(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Workspace, ToolError> {
+        workspace::force_unlock(
+            workspace_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RunApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl RunApi<'_> {
+    pub async fn create(
+        &self,
+        workspace_id: &str,
+        attributes: Option<RunAttributes>,
+    ) -> Result<Run, ToolError> {
+        run::create(
+            workspace_id,
+            attributes,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn status(&self, run_id: &str) -> Result<Run, ToolError> {
+        run::status(run_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn cancel(&self, run_id: &str) -> Result<(), ToolError> {
+        run::cancel(run_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn discard(&self, run_id: &str) -> Result<(), ToolError> {
+        run::discard(run_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn apply(
+        &self,
+        run_id: &str,
+        comment: Option<String>,
+    ) -> Result<(), ToolError> {
+        run::apply(run_id, comment, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn work_queue(
+        &self,
+        workspaces: Vec<Workspace>,
+        options: QueueOptions,
+        attributes: RunAttributes,
+    ) -> Result<QueueResult, ToolError> {
+        run::work_queue(
+            workspaces,
+            options,
+            attributes,
+            self.tfc.client.clone(),
+            &self.tfc.config,
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlanApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl PlanApi<'_> {
+    pub async fn show(&self, plan_id: &str) -> Result<Plan, ToolError> {
+        plan::show(plan_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn show_by_run(&self, run_id: &str) -> Result<Plan, ToolError> {
+        plan::show_by_run(run_id, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn json_output(
+        &self,
+        plan_id: &str,
+    ) -> Result<PlanJson, ToolError> {
+        plan::json_output(plan_id, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn summary(
+        &self,
+        plan_id: &str,
+    ) -> Result<ChangeSummary, ToolError> {
+        plan::summary(plan_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn logs(&self, plan_id: &str) -> Result<String, ToolError> {
+        plan::logs(plan_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn follow_logs<C: FnMut(&str)>(
+        &self,
+        plan_id: &str,
+        poll_interval: Duration,
+        on_chunk: C,
+    ) -> Result<(), ToolError> {
+        plan::follow_logs(
+            plan_id,
+            poll_interval,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+            on_chunk,
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ApplyApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl ApplyApi<'_> {
+    pub async fn show(&self, apply_id: &str) -> Result<Apply, ToolError> {
+        apply::show(apply_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn show_by_run(&self, run_id: &str) -> Result<Apply, ToolError> {
+        apply::show_by_run(run_id, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn logs(&self, apply_id: &str) -> Result<String, ToolError> {
+        apply::logs(apply_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn follow_logs<C: FnMut(&str)>(
+        &self,
+        apply_id: &str,
+        poll_interval: Duration,
+        on_chunk: C,
+    ) -> Result<(), ToolError> {
+        apply::follow_logs(
+            apply_id,
+            poll_interval,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+            on_chunk,
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StateVersionApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl StateVersionApi<'_> {
+    pub async fn current(
+        &self,
+        workspace_id: &str,
+    ) -> Result<StateVersion, ToolError> {
+        state_version::current(
+            workspace_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn list(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<StateVersion>, ToolError> {
+        state_version::list(
+            workspace_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn outputs(
+        &self,
+        state_version_id: &str,
+    ) -> Result<Vec<Output>, ToolError> {
+        state_version::outputs(
+            state_version_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn download(
+        &self,
+        state_version: &StateVersion,
+    ) -> Result<Vec<u8>, ToolError> {
+        state_version::download(state_version, self.tfc.client.clone()).await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VariableApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl VariableApi<'_> {
+    pub async fn create(
+        &self,
+        workspace_id: &str,
+        var: Variable,
+    ) -> Result<Variable, ToolError> {
+        variable::create(
+            workspace_id,
+            var,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn update(
+        &self,
+        workspace_id: &str,
+        variable_id: &str,
+        attributes: VariableAttributes,
+    ) -> Result<Variable, ToolError> {
+        variable::update(
+            workspace_id,
+            variable_id,
+            attributes,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn upsert(
+        &self,
+        workspace_id: &str,
+        var: Variable,
+    ) -> Result<Variable, ToolError> {
+        variable::upsert(
+            workspace_id,
+            var,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn upsert_all(
+        &self,
+        workspace_id: &str,
+        vars: Vec<Variable>,
+    ) -> Result<Vec<Variable>, ToolError> {
+        variable::upsert_all(
+            workspace_id,
+            vars,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn list(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<Variable>, ToolError> {
+        variable::list(workspace_id, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn list_batch(
+        &self,
+        workspaces: Vec<Workspace>,
+    ) -> VariablesBatch {
+        variable::list_batch(
+            &self.tfc.config,
+            self.tfc.client.clone(),
+            workspaces,
+        )
+        .await
+    }
+
+    pub async fn delete(
+        &self,
+        variable_id: &str,
+        workspace_id: &str,
+    ) -> Result<(), ToolError> {
+        variable::delete(
+            variable_id,
+            workspace_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TagApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl TagApi<'_> {
+    pub async fn list(&self, workspace_id: &str) -> Result<Tags, ToolError> {
+        tag::list(workspace_id, &self.tfc.config, self.tfc.client.clone()).await
+    }
+
+    pub async fn list_by_name(
+        &self,
+        workspace_name: &str,
+    ) -> Result<Tags, ToolError> {
+        tag::list_by_name(
+            workspace_name,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn add(
+        &self,
+        workspace_id: &str,
+        tags: Vec<String>,
+    ) -> Result<(), ToolError> {
+        tag::add(workspace_id, tags, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn add_by_name(
+        &self,
+        workspace_name: &str,
+        tags: Vec<String>,
+    ) -> Result<(), ToolError> {
+        tag::add_by_name(
+            workspace_name,
+            tags,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn remove(
+        &self,
+        workspace_id: &str,
+        tags: Vec<String>,
+    ) -> Result<(), ToolError> {
+        tag::remove(
+            workspace_id,
+            tags,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn remove_by_name(
+        &self,
+        workspace_name: &str,
+        tags: Vec<String>,
+    ) -> Result<(), ToolError> {
+        tag::remove_by_name(
+            workspace_name,
+            tags,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VariableSetApi<'a> {
+    tfc: &'a TfcClient,
+}
+
+impl VariableSetApi<'_> {
+    pub async fn show(
+        &self,
+        variable_set_id: &str,
+    ) -> Result<VarSet, ToolError> {
+        variable_set::show(
+            variable_set_id,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn list_by_org(&self) -> Result<VarSets, ToolError> {
+        variable_set::list_by_org(&self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn list_by_project(&self) -> Result<VarSets, ToolError> {
+        variable_set::list_by_project(&self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn create(
+        &self,
+        options: VarSetOptions,
+    ) -> Result<(), ToolError> {
+        variable_set::create(options, &self.tfc.config, self.tfc.client.clone())
+            .await
+    }
+
+    pub async fn apply_workspace(
+        &self,
+        variable_set_id: &str,
+        workspaces: Vec<Workspace>,
+    ) -> Result<(), ToolError> {
+        variable_set::apply_workspace(
+            variable_set_id,
+            workspaces,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+
+    pub async fn remove_workspace(
+        &self,
+        variable_set_id: &str,
+        workspaces: Vec<Workspace>,
+    ) -> Result<(), ToolError> {
+        variable_set::remove_workspace(
+            variable_set_id,
+            workspaces,
+            &self.tfc.config,
+            self.tfc.client.clone(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{error::ToolError, settings::Core};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use url::Url;
+
+pub const TOKEN_ENV_PREFIX: &str = "TF_TOKEN_";
+
+/// Where the API token was found
+///
+/// When `token` is not set directly the other sources are tried in the
+/// order they are listed here.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenSource {
+    /// `token` from a cli argument, the settings file or the environment
+    Config,
+    /// The output of `token_command`
+    Command,
+    /// A `TF_TOKEN_<host>` environment variable, as read by Terraform
+    Environment,
+    /// The credentials file written by `terraform login`
+    CredentialsFile,
+}
+
+impl Display for TokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let source = match self {
+            TokenSource::Config => "token setting",
+            TokenSource::Command => "token command",
+            TokenSource::Environment => "TF_TOKEN environment variable",
+            TokenSource::CredentialsFile => "Terraform credentials file",
+        };
+        write!(f, "{}", source)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CredentialsFile {
+    #[serde(default)]
+    credentials: BTreeMap<String, Credential>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Credential {
+    token: String,
+}
+
+/// The host name of the API, used to look up credentials for it, along with
+/// the port when it isn't the default for the scheme, as Terraform does
+pub fn host(base_url: &str) -> Result<String, ToolError> {
+    let url = Url::parse(base_url)?;
+    let host = url.host_str().map(str::to_lowercase).ok_or_else(|| {
+        ToolError::General(anyhow::anyhow!(
+            "Base URL {} does not have a host",
+            base_url
+        ))
+    })?;
+    match url.port() {
+        Some(port) => Ok(format!("{}:{}", host, port)),
+        None => Ok(host),
+    }
+}
+
+/// The environment variable Terraform reads a token for the host from,
+/// periods are replaced by underscores and dashes by double underscores
+///
+/// Any port is left out, a name containing `:` can't be set from a shell.
+pub fn env_var_name(host: &str) -> String {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    format!("{}{}", TOKEN_ENV_PREFIX, name.replace('-', "__").replace('.', "_"))
+}
+
+/// The location of the credentials file written by `terraform login`
+pub fn credentials_file() -> Option<PathBuf> {
+    if cfg!(windows) {
+        dirs::config_dir().map(|dir| dir.join("terraform.d"))
+    } else {
+        dirs::home_dir().map(|dir| dir.join(".terraform.d"))
+    }
+    .map(|dir| dir.join("credentials.tfrc.json"))
+}
+
+/// Read the token for the host from a Terraform credentials file
+pub fn from_credentials_file(
+    path: &Path,
+    host: &str,
+) -> Result<Option<String>, ToolError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    let file: CredentialsFile =
+        serde_json::from_str(&contents).map_err(|source| {
+            ToolError::InvalidCredentialsFile {
+                path: path.to_path_buf(),
+                source,
+            }
+        })?;
+    Ok(file
+        .credentials
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(host))
+        .map(|(_, credential)| credential.token))
+}
+
+/// Run the token command through the shell and read the token from its output
+pub fn from_command(command: &str) -> Result<String, ToolError> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()?
+    } else {
+        Command::new("sh").args(["-c", command]).output()?
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let mut message =
+            format!("`{}` exited with {}", command, output.status);
+        if !stderr.trim().is_empty() {
+            message = format!("{}: {}", message, stderr.trim());
+        }
+        return Err(ToolError::TokenCommand(message));
+    }
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(ToolError::TokenCommand(format!(
+            "`{}` did not output a token",
+            command
+        )));
+    }
+    Ok(token)
+}
+
+/// Find a token from the sources other than the `token` setting, in order
+/// of precedence: the token command, a `TF_TOKEN_<host>` environment
+/// variable, then the Terraform credentials file
+pub fn resolve(
+    config: &Core,
+) -> Result<Option<(String, TokenSource)>, ToolError> {
+    let command = config.token_command.as_deref().filter(|c| !c.is_empty());
+    if let Some(command) = command {
+        debug!("Reading token from command.");
+        return Ok(Some((from_command(command)?, TokenSource::Command)));
+    }
+    let host = host(&config.base_url)?;
+    let var = env_var_name(&host);
+    if let Ok(token) = std::env::var(&var) {
+        if !token.is_empty() {
+            debug!("Reading token from {}.", var);
+            return Ok(Some((token, TokenSource::Environment)));
+        }
+    }
+    if let Some(path) = credentials_file() {
+        if let Some(token) = from_credentials_file(&path, &host)? {
+            debug!("Reading token for {} from {}.", host, path.display());
+            return Ok(Some((token, TokenSource::CredentialsFile)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts() {
+        let cases = [
+            ("https://app.terraform.io/api/v2", "app.terraform.io"),
+            ("https://TFE.Example.com/api/v2", "tfe.example.com"),
+            ("https://tfe.example.com:443/api/v2", "tfe.example.com"),
+            ("https://tfe.example.com:8443/api/v2", "tfe.example.com:8443"),
+            ("http://localhost:8080", "localhost:8080"),
+        ];
+        for (base_url, expected) in cases {
+            assert_eq!(host(base_url).unwrap(), expected, "{}", base_url);
+        }
+        assert!(host("not a url").is_err());
+        assert!(host("unix:/run/tfe.sock").is_err());
+    }
+
+    #[test]
+    fn env_var_names() {
+        let cases = [
+            ("app.terraform.io", "TF_TOKEN_app_terraform_io"),
+            ("my-tfe.example.com", "TF_TOKEN_my__tfe_example_com"),
+            ("tfe.example.com:8443", "TF_TOKEN_tfe_example_com"),
+            ("localhost:8080", "TF_TOKEN_localhost"),
+        ];
+        for (host, expected) in cases {
+            assert_eq!(env_var_name(host), expected);
+        }
+    }
+
+    #[test]
+    fn credentials_file_lookup() {
+        let path = std::env::temp_dir()
+            .join(format!("tfc-toolset-{}.tfrc.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{
+  "credentials": {
+    "app.terraform.io": { "token": "cloud-token" },
+    "TFE.example.com:8443": { "token": "tfe-token" }
+  }
+}"#,
+        )
+        .unwrap();
+        let lookup = |host: &str| from_credentials_file(&path, host).unwrap();
+        let found = (
+            lookup("app.terraform.io"),
+            lookup("tfe.example.com:8443"),
+            lookup("tfe.example.com"),
+        );
+        fs::write(&path, "not json").unwrap();
+        let invalid = from_credentials_file(&path, "app.terraform.io");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(found.0.as_deref(), Some("cloud-token"));
+        assert_eq!(found.1.as_deref(), Some("tfe-token"));
+        assert_eq!(found.2, None);
+        assert!(matches!(
+            invalid,
+            Err(ToolError::InvalidCredentialsFile { .. })
+        ));
+        assert_eq!(
+            from_credentials_file(&path, "app.terraform.io").unwrap(),
+            None
+        );
+    }
+}
@@ -1,11 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use surf::{http::StatusCode, Response};
 use thiserror::Error;
 
-pub const SETTINGS_ERROR: &str = "Uh Oh, looks like a settings issue! By default I look for a settings.toml file and override with env variables.";
+pub const SETTINGS_ERROR: &str = "Uh Oh, looks like a settings issue! By default I look for a settings.toml file, then $XDG_CONFIG_HOME/tfct/config.toml, and override with env variables.";
 
 pub(crate) fn surf_to_tool_error(e: surf::Error) -> ToolError {
     ToolError::General(e.into_inner())
 }
 
+/// Build an error from an unsuccessful API response, parsing any JSON:API
+/// `errors` from the body
+pub(crate) async fn response_to_tool_error(res: &mut Response) -> ToolError {
+    let body = match res.body_string().await {
+        Ok(body) => body,
+        Err(e) => return surf_to_tool_error(e),
+    };
+    let errors = match serde_json::from_str::<ApiErrors>(&body) {
+        Ok(api_errors) => api_errors.errors,
+        Err(_) => vec![ApiError {
+            status: Some(res.status().to_string()),
+            title: Some(res.status().canonical_reason().to_string()),
+            detail: if body.is_empty() { None } else { Some(body) },
+            source: None,
+        }],
+    };
+    ToolError::Api { status: res.status().into(), errors }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApiErrorSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+}
+
+/// A single entry from the JSON:API `errors` array
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApiError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<ApiErrorSource>,
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.title, &self.detail) {
+            (Some(title), Some(detail)) => write!(f, "{}: {}", title, detail)?,
+            (Some(message), None) | (None, Some(message)) => {
+                write!(f, "{}", message)?
+            }
+            (None, None) => write!(f, "unknown error")?,
+        }
+        if let Some(pointer) =
+            self.source.as_ref().and_then(|s| s.pointer.as_ref())
+        {
+            write!(f, " (at {})", pointer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ApiErrors {
+    errors: Vec<ApiError>,
+}
+
+fn display_api_errors(errors: &[ApiError]) -> String {
+    errors.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("; ")
+}
+
 /// A generic “error” type
 #[derive(Error, Debug)]
 pub enum ToolError {
@@ -27,16 +97,96 @@ pub enum ToolError {
     /// Error parsing boolean value
     #[error(transparent)]
     Bool(#[from] std::str::ParseBoolError),
+    /// Invalid variable category
+    #[error("Invalid variable category: {0}. Expected terraform or env")]
+    InvalidVariableCategory(String),
     /// Invalid variable query format
     #[error("Invalid variable query format: {0}. Expected format: key:operator:value")]
     InvalidVariableQuery(String),
     /// Invalid tag query format
     #[error("Invalid tag query format: {0}. Expected format: operator:name")]
     InvalidTagQuery(String),
+    /// Invalid attribute query format
+    #[error("Invalid attribute query format: {0}. Expected format: attribute:operator:value")]
+    InvalidAttributeQuery(String),
+    /// Invalid workspace attribute in a query
+    #[error("Invalid query attribute: {0}. Expected one of: name, execution_mode, terraform_version, auto_apply, vcs_repo.identifier, working_directory, agent_pool_id, has_vcs_repo, latest_run.status, latest_run.age, updated_at.age")]
+    InvalidQueryAttribute(String),
     /// Invalid query operator
-    #[error("Invalid query operator: {0}. Expected one of: ==, !=, ~=, !~=")]
+    #[error("Invalid query operator: {0}. Expected one of: ==, !=, ~=, !~=, =~, !=~, *=, !*=, <, <=, >, >=")]
     InvalidQueryOperator(String),
+    /// Invalid query expression
+    #[error("Invalid query expression: {0}")]
+    InvalidQueryExpression(String),
+    /// Invalid version in a query comparison
+    #[error(
+        "Invalid query version: {0}. Expected a version such as 1.5 or 1.5.7"
+    )]
+    InvalidQueryVersion(String),
+    /// Invalid age in a query comparison
+    #[error("Invalid query age: {0}. Expected a comparison with <, <=, > or >= and an age such as 30m, 12h, 90d or 2w")]
+    InvalidQueryAge(String),
+    /// Invalid run status in a query
+    #[error("Invalid run status: {0}")]
+    InvalidRunStatus(String),
+    /// Invalid HTTP cache mode
+    #[error("Invalid cache mode: {0}. Valid modes are default, no-store, reload, no-cache, force-cache and only-if-cached")]
+    InvalidCacheMode(String),
+    /// The token command failed or did not output a token
+    #[error("Token command failed: {0}")]
+    TokenCommand(String),
+    /// The Terraform credentials file could not be parsed
+    #[error("Unable to read credentials from {path}: {source}")]
+    InvalidCredentialsFile {
+        path: std::path::PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// Invalid regex or glob pattern in a query
+    #[error("Invalid query pattern {pattern}: {source}")]
+    InvalidQueryPattern {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
     /// Pagination error
     #[error("Pagination error: {0}")]
     Pagination(String),
+    /// Unsuccessful response from the API
+    #[error("API request failed with status {status}: {}", display_api_errors(.errors))]
+    Api { status: u16, errors: Vec<ApiError> },
+}
+
+impl ToolError {
+    /// The HTTP status code if this error came from an API response
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ToolError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    fn has_status(&self, code: StatusCode) -> bool {
+        self.status() == Some(code.into())
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.has_status(StatusCode::Unauthorized)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.has_status(StatusCode::NotFound)
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.has_status(StatusCode::Conflict)
+    }
+
+    pub fn is_unprocessable(&self) -> bool {
+        self.has_status(StatusCode::UnprocessableEntity)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.has_status(StatusCode::TooManyRequests)
+    }
 }
@@ -0,0 +1,592 @@
+use crate::{
+    error::ToolError,
+    filter::workspace::{matches_attribute, matches_tag, matches_variable},
+    settings::{Attribute, Operators, Tag, Variable, WorkspaceAttribute},
+    variable,
+    workspace::Workspace,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// A single comparison in a query expression
+#[derive(Clone, Debug)]
+pub enum Predicate {
+    /// `<attribute> <operator> value`, compared against a workspace attribute
+    /// such as `name` or `execution_mode`
+    Attribute(Attribute),
+    /// `tag <operator> value`, compared against each of the workspace tags
+    Tag(Tag),
+    /// `var.<key> <operator> value`, compared against the workspace variable
+    Variable(Variable),
+}
+
+impl Predicate {
+    pub fn evaluate(
+        &self,
+        workspace: &Workspace,
+        variables: &[variable::Variable],
+    ) -> bool {
+        match self {
+            Predicate::Attribute(attribute) => {
+                matches_attribute(workspace, attribute)
+            }
+            Predicate::Tag(tag) => matches_tag(workspace, tag),
+            Predicate::Variable(var) => matches_variable(variables, var),
+        }
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Predicate::Attribute(attribute) => write!(
+                f,
+                "{} {} {}",
+                attribute.attribute,
+                attribute.operator,
+                quote(&attribute.value)
+            ),
+            Predicate::Tag(tag) => {
+                write!(f, "tag {} {}", tag.operator, quote(&tag.name))
+            }
+            Predicate::Variable(var) => write!(
+                f,
+                "var.{} {} {}",
+                var.key,
+                var.operator,
+                quote(&var.value)
+            ),
+        }
+    }
+}
+
+/// A boolean query expression used to filter workspaces
+///
+/// Predicates can be combined with `AND`, `OR` and `NOT` along with
+/// parentheses for grouping, `NOT` binds tightest followed by `AND` then
+/// `OR`. For example:
+///
+/// `tag == team:infra AND (var.env == prod OR var.env == staging) AND NOT name ~= sandbox`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Predicate(Predicate),
+}
+
+impl Expr {
+    /// Evaluate the expression against a workspace and its variables
+    pub fn evaluate(
+        &self,
+        workspace: &Workspace,
+        variables: &[variable::Variable],
+    ) -> bool {
+        match self {
+            Expr::And(left, right) => {
+                left.evaluate(workspace, variables)
+                    && right.evaluate(workspace, variables)
+            }
+            Expr::Or(left, right) => {
+                left.evaluate(workspace, variables)
+                    || right.evaluate(workspace, variables)
+            }
+            Expr::Not(expr) => !expr.evaluate(workspace, variables),
+            Expr::Predicate(predicate) => {
+                predicate.evaluate(workspace, variables)
+            }
+        }
+    }
+
+    /// Whether the expression refers to any workspace variables, if not there
+    /// is no need to retrieve them
+    pub fn has_variables(&self) -> bool {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.has_variables() || right.has_variables()
+            }
+            Expr::Not(expr) => expr.has_variables(),
+            Expr::Predicate(predicate) => {
+                matches!(predicate, Predicate::Variable(_))
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ToolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let expr = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(expr)
+    }
+}
+
+impl TryFrom<String> for Expr {
+    type Error = ToolError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Expr::from_str(&s)
+    }
+}
+
+impl From<Expr> for String {
+    fn from(expr: Expr) -> Self {
+        expr.to_string()
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Or(left, right) => write!(f, "{} OR {}", left, right),
+            Expr::And(left, right) => {
+                write_grouped(f, left, matches!(**left, Expr::Or(..)))?;
+                write!(f, " AND ")?;
+                write_grouped(f, right, matches!(**right, Expr::Or(..)))
+            }
+            Expr::Not(expr) => {
+                write!(f, "NOT ")?;
+                write_grouped(f, expr, !matches!(**expr, Expr::Predicate(_)))
+            }
+            Expr::Predicate(predicate) => write!(f, "{}", predicate),
+        }
+    }
+}
+
+fn write_grouped(
+    f: &mut Formatter<'_>,
+    expr: &Expr,
+    group: bool,
+) -> std::fmt::Result {
+    if group {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+// Quote a value if it could not be read back as a bare word
+fn quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains(is_reserved) {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Characters that end a bare word or start a quoted one
+fn is_reserved(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\'')
+}
+
+fn is_field_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+// A recursive descent parser for query expressions
+//
+// expr      = and ( "OR" and )*
+// and       = not ( "AND" not )*
+// not       = "NOT" not | primary
+// primary   = "(" expr ")" | predicate
+// predicate = field operator value
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ToolError {
+        ToolError::InvalidQueryExpression(format!(
+            "{} at position {} in `{}`",
+            message, self.pos, self.input
+        ))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    // Consume a keyword if it is next, keywords are case insensitive and must
+    // be followed by whitespace or a parenthesis
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.len() < keyword.len()
+            || !rest.is_char_boundary(keyword.len())
+            || !rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+        {
+            return false;
+        }
+        match rest[keyword.len()..].chars().next() {
+            Some(c) if !c.is_whitespace() && c != '(' => false,
+            _ => {
+                self.pos += keyword.len();
+                true
+            }
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ToolError> {
+        let mut expr = self.parse_and()?;
+        while self.keyword("OR") {
+            let right = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ToolError> {
+        let mut expr = self.parse_not()?;
+        while self.keyword("AND") {
+            let right = self.parse_not()?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ToolError> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ToolError> {
+        self.skip_whitespace();
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            self.skip_whitespace();
+            if self.peek() != Some(')') {
+                return Err(self.error("expected `)`"));
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        self.parse_predicate().map(Expr::Predicate)
+    }
+
+    fn parse_predicate(&mut self) -> Result<Predicate, ToolError> {
+        let start = self.pos;
+        let field = self.take_while(is_field_char);
+        // A keyword here means a predicate is missing, e.g. `a == b OR OR`
+        let is_keyword =
+            ["AND", "OR"].iter().any(|k| field.eq_ignore_ascii_case(k));
+        if field.is_empty() || is_keyword {
+            self.pos = start;
+            return Err(
+                self.error("expected an attribute, `tag` or `var.<key>`")
+            );
+        }
+        self.skip_whitespace();
+        let operator =
+            self.take_while(|c| matches!(c, '=' | '!' | '~' | '*' | '<' | '>'));
+        if operator.is_empty() {
+            return Err(self.error("expected an operator"));
+        }
+        let operator = Operators::from_str(operator)?;
+        let value = self.parse_value()?;
+        if field == "tag" {
+            return Ok(Predicate::Tag(Tag::new(operator, &value)?));
+        }
+        if let Some(key) = field.strip_prefix("var.") {
+            if !key.is_empty() {
+                let variable = Variable::new(key, operator, &value)?;
+                return Ok(Predicate::Variable(variable));
+            }
+        }
+        match WorkspaceAttribute::from_str(field) {
+            Ok(attribute) => Ok(Predicate::Attribute(Attribute::new(
+                attribute, operator, &value,
+            )?)),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error(&format!("unknown field `{}`", field)))
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String, ToolError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let mut value = String::new();
+                let mut chars = self.rest().char_indices();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        c if c == quote => {
+                            self.pos += i + 1;
+                            return Ok(value);
+                        }
+                        c => value.push(c),
+                    }
+                }
+                Err(self.error("unterminated quoted value"))
+            }
+            _ => {
+                let value = self.take_while(|c| !is_reserved(c));
+                if value.is_empty() {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(value.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Write the expression with every group in parentheses to show how it
+    // was parsed
+    fn shape(expr: &Expr) -> String {
+        match expr {
+            Expr::Or(left, right) => {
+                format!("({} OR {})", shape(left), shape(right))
+            }
+            Expr::And(left, right) => {
+                format!("({} AND {})", shape(left), shape(right))
+            }
+            Expr::Not(expr) => format!("NOT {}", shape(expr)),
+            Expr::Predicate(predicate) => predicate.to_string(),
+        }
+    }
+
+    fn parse(input: &str) -> String {
+        shape(&Expr::from_str(input).unwrap())
+    }
+
+    fn error(input: &str) -> String {
+        Expr::from_str(input).unwrap_err().to_string()
+    }
+
+    fn value(input: &str) -> String {
+        match Expr::from_str(input).unwrap() {
+            Expr::Predicate(Predicate::Attribute(attribute)) => attribute.value,
+            Expr::Predicate(Predicate::Tag(tag)) => tag.name,
+            Expr::Predicate(Predicate::Variable(var)) => var.value,
+            expr => panic!("expected a predicate, got {}", expr),
+        }
+    }
+
+    #[test]
+    fn predicates() {
+        let cases = [
+            ("name == app", "name == app"),
+            ("  name==app  ", "name == app"),
+            ("tag == team:infra", "tag == team:infra"),
+            ("var.env != prod", "var.env != prod"),
+            ("var.AWS_REGION =~ ^us-", "var.AWS_REGION =~ ^us-"),
+            ("execution_mode ~= remote", "execution_mode ~= remote"),
+            ("terraform_version < 1.5", "terraform_version < 1.5"),
+            ("latest_run.age > 30d", "latest_run.age > 30d"),
+            ("name *= *-prod", "name *= *-prod"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(input), expected, "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        let cases = [
+            // AND binds tighter than OR
+            (
+                "name == a OR name == b AND name == c",
+                "(name == a OR (name == b AND name == c))",
+            ),
+            (
+                "name == a AND name == b OR name == c",
+                "((name == a AND name == b) OR name == c)",
+            ),
+            // NOT binds tighter than AND
+            ("NOT name == a AND name == b", "(NOT name == a AND name == b)"),
+            ("name == a OR NOT name == b", "(name == a OR NOT name == b)"),
+            ("NOT NOT name == a", "NOT NOT name == a"),
+            // Operators of the same precedence group to the left
+            (
+                "name == a OR name == b OR name == c",
+                "((name == a OR name == b) OR name == c)",
+            ),
+            (
+                "name == a AND name == b AND name == c",
+                "((name == a AND name == b) AND name == c)",
+            ),
+            // Keywords are case insensitive
+            (
+                "name == a or not name == b and name == c",
+                "(name == a OR (NOT name == b AND name == c))",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(input), expected, "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn parentheses() {
+        let cases = [
+            ("(name == a)", "name == a"),
+            ("((name == a))", "name == a"),
+            (
+                "(name == a OR name == b) AND name == c",
+                "((name == a OR name == b) AND name == c)",
+            ),
+            (
+                "name == a AND (name == b OR name == c)",
+                "(name == a AND (name == b OR name == c))",
+            ),
+            ("NOT (name == a OR name == b)", "NOT (name == a OR name == b)"),
+            ("NOT(name == a)", "NOT name == a"),
+            ("(name == a)AND(name == b)", "(name == a AND name == b)"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(input), expected, "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn quoting() {
+        let cases = [
+            (r#"name == "my workspace""#, "my workspace"),
+            ("name == 'my workspace'", "my workspace"),
+            (r#"name == "say \"hi\"""#, r#"say "hi""#),
+            (r#"name == 'it\'s'"#, "it's"),
+            (r#"name == "back\\slash""#, r"back\slash"),
+            (r#"name == "(grouped) AND OR""#, "(grouped) AND OR"),
+            (r#"name == """#, ""),
+            (r#"name == "it's""#, "it's"),
+            // A bare value ends at whitespace or a parenthesis
+            ("name == AND", "AND"),
+            (
+                "var.url == https://example.com/a?b=c",
+                "https://example.com/a?b=c",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(value(input), expected, "parsing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let inputs = [
+            "name == a OR name == b AND NOT name == c",
+            "(name == a OR name == b) AND NOT (tag == x OR var.env != prod)",
+            r#"name == "my workspace" AND tag == "quote \" and \\ slash""#,
+            r#"var.note == "" OR name == "(x)""#,
+            "NOT NOT name == a",
+        ];
+        for input in inputs {
+            let expr = Expr::from_str(input).unwrap();
+            let reparsed = Expr::from_str(&expr.to_string()).unwrap();
+            assert_eq!(
+                shape(&reparsed),
+                shape(&expr),
+                "round trip {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn has_variables() {
+        let cases = [
+            ("name == a", false),
+            ("var.env == prod", true),
+            ("name == a AND tag == b", false),
+            ("name == a OR NOT var.env == prod", true),
+        ];
+        for (input, expected) in cases {
+            let expr = Expr::from_str(input).unwrap();
+            assert_eq!(expr.has_variables(), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        let cases = [
+            ("", "expected an attribute, `tag` or `var.<key>` at position 0 in ``"),
+            ("name", "expected an operator at position 4 in `name`"),
+            ("name bar", "expected an operator at position 5 in `name bar`"),
+            ("name ==", "expected a value at position 7 in `name ==`"),
+            ("name == (", "expected a value at position 8 in `name == (`"),
+            ("foo == bar", "unknown field `foo` at position 0 in `foo == bar`"),
+            ("var. == bar", "unknown field `var.` at position 0 in `var. == bar`"),
+            ("(name == a", "expected `)` at position 10 in `(name == a`"),
+            ("name == a)", "unexpected input at position 9 in `name == a)`"),
+            ("name == a b", "unexpected input at position 10 in `name == a b`"),
+            ("name == a AND", "expected an attribute, `tag` or `var.<key>` at position 13 in `name == a AND`"),
+            ("name == a OR OR name == b", "expected an attribute, `tag` or `var.<key>` at position 13 in `name == a OR OR name == b`"),
+            ("NOT", "expected an attribute, `tag` or `var.<key>` at position 3 in `NOT`"),
+            ("name == a ANDname == b", "unexpected input at position 10 in `name == a ANDname == b`"),
+            ("()", "expected an attribute, `tag` or `var.<key>` at position 1 in `()`"),
+            (r#"name == "open"#, "unterminated quoted value at position 9 in `name == \"open`"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                error(input),
+                format!("Invalid query expression: {}", expected),
+                "parsing {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_predicates() {
+        assert!(matches!(
+            Expr::from_str("name =! a"),
+            Err(ToolError::InvalidQueryOperator(op)) if op == "=!"
+        ));
+        assert!(matches!(
+            Expr::from_str(r#"tag =~ "(unclosed""#),
+            Err(ToolError::InvalidQueryPattern { .. })
+        ));
+        assert!(matches!(
+            Expr::from_str("terraform_version < latest"),
+            Err(ToolError::InvalidQueryVersion(_))
+        ));
+    }
+}
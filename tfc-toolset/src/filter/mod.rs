@@ -1 +1,2 @@
+pub mod expr;
 pub mod workspace;
@@ -1,99 +1,71 @@
 use crate::{
     error::ToolError,
-    settings::{Core, Operators},
+    settings::{Attribute, Core, Tag, Variable},
+    variable,
     workspace::{Workspace, WorkspaceVariables},
 };
 
+/// Whether the variables of a workspace satisfy a variable query
+pub fn matches_variable(
+    variables: &[variable::Variable],
+    query: &Variable,
+) -> bool {
+    // Is there a variable with the query key whose value matches?
+    let hit = variables.iter().any(|var| {
+        var.attributes.key == query.key
+            && var
+                .attributes
+                .value
+                .as_ref()
+                .map(|value| query.is_match(value))
+                .unwrap_or(false)
+    });
+    // Positive operators require a hit, so a missing variable is
+    // removed. Negated operators remove on a hit, so a missing
+    // variable is kept.
+    hit != query.operator.is_negated()
+}
+
+/// Whether the tags of a workspace satisfy a tag query
+pub fn matches_tag(workspace: &Workspace, query: &Tag) -> bool {
+    let hit = workspace
+        .attributes
+        .tag_names
+        .as_ref()
+        .map(|names| names.iter().any(|name| query.is_match(name)))
+        .unwrap_or(false);
+    hit != query.operator.is_negated()
+}
+
+/// Whether the attributes of a workspace satisfy an attribute query
+pub fn matches_attribute(workspace: &Workspace, query: &Attribute) -> bool {
+    // An unset attribute is treated the same as a missing variable
+    let hit = query
+        .attribute
+        .value(workspace)
+        .map(|value| query.is_match(&value))
+        .unwrap_or(false);
+    hit != query.operator.is_negated()
+}
+
 pub fn by_variable(
     workspace_variables: &mut Vec<WorkspaceVariables>,
     config: &Core,
 ) -> Result<(), ToolError> {
+    let variables = match &config.workspaces.query {
+        Some(query) => match &query.variables {
+            Some(variables) => variables,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
     // You have to return a bool from the closure.
     // If you return true, the element is not removed;
     // if you return false, it is removed.
     workspace_variables.retain(|workspace| {
-        let mut keep = false;
-        if let Some(query) = config.clone().workspaces.query {
-            if let Some(variables) = query.variables {
-                for variable in variables {
-                    match variable.operator {
-                        Operators::Equals => {
-                            // Should be equal.
-                            // so if it's not equal or not present we need to remove.
-                            keep = false;
-                            for var in workspace.variables.clone() {
-                                if var.attributes.key == variable.key {
-                                    if let Some(value) = var.attributes.value {
-                                        if value == variable.value {
-                                            keep = true;
-                                        }
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::NotEquals => {
-                            // Should not be equal.
-                            // so if it's equal we need to remove.
-                            keep = true;
-                            for var in workspace.variables.clone() {
-                                if var.attributes.key == variable.key {
-                                    if let Some(value) = var.attributes.value {
-                                        if value == variable.value {
-                                            keep = false;
-                                        }
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::Contains => {
-                            // Should contain the query value.
-                            // so if it doesn't contain the value or isn't present we need to remove.
-                            keep = false;
-                            for var in workspace.variables.clone() {
-                                if var.attributes.key == variable.key {
-                                    if let Some(value) = var.attributes.value {
-                                        if value
-                                            .contains(&variable.value.clone())
-                                        {
-                                            keep = true;
-                                        }
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::NotContains => {
-                            // Should be a regex hit.
-                            // so if it's not a hit or not present we need to remove.
-                            keep = true;
-                            for var in workspace.variables.clone() {
-                                if var.attributes.key == variable.key {
-                                    if let Some(value) = var.attributes.value {
-                                        if value
-                                            .contains(&variable.value.clone())
-                                        {
-                                            keep = false;
-                                        }
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        keep
+        variables
+            .iter()
+            .all(|variable| matches_variable(&workspace.variables, variable))
     });
     Ok(())
 }
@@ -102,85 +74,55 @@ pub fn by_tag(
     workspaces: &mut Vec<Workspace>,
     config: &Core,
 ) -> Result<(), ToolError> {
-    // You have to return a bool from the closure.
-    // If you return true, the element is not removed;
-    // if you return false, it is removed.
+    let tags = match &config.workspaces.query {
+        Some(query) => match &query.tags {
+            Some(tags) => tags,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    // Equals and NotEquals are also handled in the url params, but
+    // checking them again here is harmless.
+    workspaces.retain(|workspace| {
+        tags.iter().all(|q_tag| matches_tag(workspace, q_tag))
+    });
+    Ok(())
+}
+
+pub fn by_attribute(
+    workspaces: &mut Vec<Workspace>,
+    config: &Core,
+) -> Result<(), ToolError> {
+    let attributes = match &config.workspaces.query {
+        Some(query) => match &query.attributes {
+            Some(attributes) => attributes,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
     workspaces.retain(|workspace| {
-        let mut keep = false;
-        if let Some(query) = config.clone().workspaces.query {
-            if let Some(tags) = query.tags {
-                for q_tag in tags {
-                    match q_tag.operator {
-                        // Equals and NotEquals is now handled in the url params so could be changed
-                        Operators::Equals => {
-                            // Should be equal.
-                            // so if it's not equal or not present we need to remove.
-                            keep = false;
-                            if let Some(tag_names) =
-                                workspace.attributes.tag_names.clone()
-                            {
-                                for tag in tag_names {
-                                    if tag == q_tag.name {
-                                        keep = true;
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::NotEquals => {
-                            // Should not be equal.
-                            // so if it's equal we need to remove.
-                            keep = true;
-                            if let Some(tag_names) =
-                                workspace.attributes.tag_names.clone()
-                            {
-                                for tag in tag_names {
-                                    if tag == q_tag.name {
-                                        keep = false;
-                                    }
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::Contains => {
-                            // Should contain the query value.
-                            // so if it doesn't contain the value or isn't present we need to remove.
-                            keep = false;
-                            while let Some(tag) =
-                                workspace.attributes.tag_names.clone()
-                            {
-                                if tag.contains(&q_tag.name) {
-                                    keep = true;
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                        Operators::NotContains => {
-                            // Should be a regex hit.
-                            // so if it's not a hit or not present we need to remove.
-                            keep = true;
-                            while let Some(tag) =
-                                workspace.attributes.tag_names.clone()
-                            {
-                                if tag.contains(&q_tag.name) {
-                                    keep = false;
-                                }
-                            }
-                            if !keep {
-                                return keep;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        keep
+        attributes
+            .iter()
+            .all(|attribute| matches_attribute(workspace, attribute))
+    });
+    Ok(())
+}
+
+/// Filter workspaces with the query expression, the variables only need to
+/// be populated when the expression refers to them
+pub fn by_expr(
+    workspace_variables: &mut Vec<WorkspaceVariables>,
+    config: &Core,
+) -> Result<(), ToolError> {
+    let expr = match &config.workspaces.query {
+        Some(query) => match &query.expr {
+            Some(expr) => expr,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    workspace_variables.retain(|workspace| {
+        expr.evaluate(&workspace.workspace, &workspace.variables)
     });
     Ok(())
 }
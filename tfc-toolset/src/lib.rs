@@ -1,15 +1,24 @@
+pub mod apply;
+pub mod client;
+pub mod credentials;
 pub mod error;
 pub mod filter;
+pub mod logs;
+pub mod pagination;
+pub mod plan;
 pub mod run;
 pub mod settings;
+pub mod state_version;
 
 pub mod tag;
 pub mod variable;
 pub mod variable_set;
 pub mod workspace;
 
+pub use client::TfcClient;
+pub use pagination::Paginator;
+
 use crate::settings::Core;
-use log::error;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use surf::{http::Method, Request, RequestBuilder};
@@ -38,18 +47,18 @@ pub struct Meta {
     pub pagination: Pagination,
 }
 
-pub(crate) fn set_page_number(page_number: u32, u: Url) -> Option<Url> {
-    let u = match Url::parse_with_params(
-        u.clone().as_str(),
-        &[("page[number]", &page_number.to_string())],
-    ) {
-        Ok(u) => u,
-        Err(e) => {
-            error!("{:#?}", e);
-            return None;
-        }
-    };
-    Some(u)
+pub(crate) fn set_page_number(page_number: u32, u: Url) -> Url {
+    let mut u = u;
+    let pairs: Vec<(String, String)> = u
+        .query_pairs()
+        .filter(|(k, _)| k != "page[number]")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    u.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("page[number]", &page_number.to_string());
+    u
 }
 
 pub(crate) fn build_request(
@@ -0,0 +1,237 @@
+use crate::{
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    run::Status,
+};
+use log::{error, info};
+use std::{future::Future, time::Duration};
+use surf::{http::Method, Client, RequestBuilder};
+use url::Url;
+
+// Control characters used by the log archive to mark the start and end of a log
+const LOG_START: char = '\u{2}';
+const LOG_END: char = '\u{3}';
+
+// The maximum number of bytes to request from the log archive at once
+const LOG_CHUNK_LIMIT: usize = 65536;
+
+// Statuses for plans and applies that indicate no more log output will follow
+pub const LOG_END_STATUSES: [Status; 5] = [
+    Status::Finished,
+    Status::Errored,
+    Status::Canceled,
+    Status::ForceCanceled,
+    Status::Unreachable,
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct LogChunk {
+    pub text: String,
+    pub bytes_read: usize,
+    pub done: bool,
+}
+
+/// Read a chunk of a log from the `log-read-url` starting at the given offset
+///
+/// A multi-byte character cut off at the end of the chunk is replaced, use
+/// `read_all` or `follow` to read a log spanning several chunks.
+pub async fn read(
+    log_read_url: &str,
+    offset: usize,
+    client: Client,
+) -> Result<LogChunk, ToolError> {
+    let bytes = read_bytes(log_read_url, offset, client).await?;
+    let mut decoder = Decoder::default();
+    let mut chunk = decoder.decode(&bytes);
+    chunk.text.push_str(&decoder.finish());
+    Ok(chunk)
+}
+
+async fn read_bytes(
+    log_read_url: &str,
+    offset: usize,
+    client: Client,
+) -> Result<Vec<u8>, ToolError> {
+    let url = Url::parse_with_params(
+        log_read_url,
+        &[
+            ("offset", offset.to_string()),
+            ("limit", LOG_CHUNK_LIMIT.to_string()),
+        ],
+    )?;
+    // The archive URL is pre-signed so no auth header is sent, and since the
+    // log grows while in progress the cache must always revalidate
+    let req = RequestBuilder::new(Method::Get, url)
+        .header("Cache-Control", "no-cache")
+        .build();
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        Ok(res.body_bytes().await.map_err(surf_to_tool_error)?)
+    } else {
+        error!("Failed to read log :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+// Decodes the chunks of a log, holding back a multi-byte character split
+// between two chunks until the rest of it has been read
+#[derive(Debug, Default)]
+struct Decoder {
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    fn decode(&mut self, bytes: &[u8]) -> LogChunk {
+        self.pending.extend_from_slice(bytes);
+        let split = self.pending.len() - incomplete_tail(&self.pending);
+        let tail = self.pending.split_off(split);
+        let mut text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending = tail;
+        let done = text.contains(LOG_END);
+        text.retain(|c| c != LOG_START && c != LOG_END);
+        LogChunk { text, bytes_read: bytes.len(), done }
+    }
+
+    // Whatever is still held back once the log has ended
+    fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        text
+    }
+}
+
+// The number of bytes at the end that start a character without completing it
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for len in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - len];
+        // Skip over continuation bytes to find the start of the character
+        if byte & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let width = match byte {
+            0xF0..=0xFF => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+        return if width > len { len } else { 0 };
+    }
+    0
+}
+
+/// Read a complete log from the `log-read-url`
+pub async fn read_all(
+    log_read_url: &str,
+    client: Client,
+) -> Result<String, ToolError> {
+    let mut log = String::new();
+    let mut decoder = Decoder::default();
+    let mut offset = 0;
+    loop {
+        let bytes = read_bytes(log_read_url, offset, client.clone()).await?;
+        let chunk = decoder.decode(&bytes);
+        log.push_str(&chunk.text);
+        offset += chunk.bytes_read;
+        if chunk.done || chunk.bytes_read < LOG_CHUNK_LIMIT {
+            break;
+        }
+    }
+    log.push_str(&decoder.finish());
+    Ok(log)
+}
+
+/// Follow a log while it is being written, passing each new chunk of text to
+/// `on_chunk`.
+///
+/// `refresh` is called before each read and should return the current
+/// `log-read-url` along with the status of the plan or apply, following stops
+/// once the log has ended or the status is one of `LOG_END_STATUSES` and
+/// there is nothing left to read.
+pub async fn follow<F, Fut, C>(
+    mut refresh: F,
+    poll_interval: Duration,
+    client: Client,
+    mut on_chunk: C,
+) -> Result<(), ToolError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(Option<String>, Status), ToolError>>,
+    C: FnMut(&str),
+{
+    let mut decoder = Decoder::default();
+    let mut offset = 0;
+    loop {
+        let (log_read_url, status) = refresh().await?;
+        let finished = LOG_END_STATUSES.contains(&status);
+        if let Some(log_read_url) = log_read_url {
+            let bytes =
+                read_bytes(&log_read_url, offset, client.clone()).await?;
+            let chunk = decoder.decode(&bytes);
+            offset += chunk.bytes_read;
+            if !chunk.text.is_empty() {
+                on_chunk(&chunk.text);
+            }
+            if chunk.done || (finished && chunk.bytes_read == 0) {
+                break;
+            }
+            if chunk.bytes_read >= LOG_CHUNK_LIMIT {
+                // There is more to read right away
+                continue;
+            }
+        } else if finished {
+            break;
+        }
+        info!("Waiting for more log output, current status: {}", status);
+        async_std::task::sleep(poll_interval).await;
+    }
+    let rest = decoder.finish();
+    if !rest.is_empty() {
+        on_chunk(&rest);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_keeps_characters_split_between_chunks() {
+        let log = "\u{2}Plan: 1 to add ✓ — done\u{3}".as_bytes();
+        for split in 0..=log.len() {
+            let mut decoder = Decoder::default();
+            let first = decoder.decode(&log[..split]);
+            let second = decoder.decode(&log[split..]);
+            let text = first.text + &second.text + &decoder.finish();
+            assert_eq!(text, "Plan: 1 to add ✓ — done", "split at {}", split);
+            assert_eq!(first.bytes_read + second.bytes_read, log.len());
+            assert!(first.done || second.done);
+        }
+    }
+
+    #[test]
+    fn decode_replaces_invalid_bytes() {
+        let mut decoder = Decoder::default();
+        let chunk = decoder.decode(b"ok \xFF ok");
+        assert_eq!(chunk.text, "ok \u{FFFD} ok");
+        assert_eq!(decoder.finish(), "");
+    }
+
+    #[test]
+    fn finish_flushes_a_truncated_character() {
+        let mut decoder = Decoder::default();
+        let chunk = decoder.decode(&"✓".as_bytes()[..2]);
+        assert_eq!(chunk.text, "");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+    }
+
+    #[test]
+    fn incomplete_tail_lengths() {
+        assert_eq!(incomplete_tail(b""), 0);
+        assert_eq!(incomplete_tail(b"abc"), 0);
+        assert_eq!(incomplete_tail("é".as_bytes()), 0);
+        assert_eq!(incomplete_tail(&"é".as_bytes()[..1]), 1);
+        assert_eq!(incomplete_tail(&"✓".as_bytes()[..2]), 2);
+        assert_eq!(incomplete_tail(&"🦀".as_bytes()[..3]), 3);
+        assert_eq!(incomplete_tail("a🦀".as_bytes()), 0);
+    }
+}
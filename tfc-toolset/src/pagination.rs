@@ -0,0 +1,139 @@
+use crate::{
+    build_request,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    set_page_number,
+    settings::Core,
+    Meta,
+};
+use futures::{
+    stream::{self, Stream},
+    TryStreamExt,
+};
+use log::{error, info};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::marker::PhantomData;
+use surf::{http::Method, Client};
+use url::Url;
+
+/// A single page of a JSON:API list response
+#[derive(Clone, Debug, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub meta: Option<Meta>,
+    /// Related resources requested with the `include` query parameter
+    #[serde(default)]
+    pub included: Vec<Value>,
+}
+
+/// Walks the pages of any JSON:API list endpoint
+///
+/// The starting page and number of pages to retrieve default to the values
+/// in `Core::pagination`, a `max_depth` of 0 will retrieve every page.
+pub struct Paginator<T> {
+    url: Url,
+    config: Core,
+    client: Client,
+    start_page: u32,
+    max_depth: u32,
+    item: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> Paginator<T> {
+    pub fn new(
+        url: Url,
+        config: &Core,
+        client: Client,
+    ) -> Result<Self, ToolError> {
+        let mut url = url;
+        url.query_pairs_mut()
+            .append_pair("page[size]", &config.pagination.page_size);
+        Ok(Self {
+            url,
+            config: config.clone(),
+            client,
+            start_page: config.pagination.start_page.parse::<u32>()?,
+            max_depth: config.pagination.max_depth.parse::<u32>()?,
+            item: PhantomData,
+        })
+    }
+
+    pub fn start_page(mut self, start_page: u32) -> Self {
+        self.start_page = start_page;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Retrieve a single page
+    pub async fn page(&self, page_number: u32) -> Result<Page<T>, ToolError> {
+        info!("Retrieving page {}.", page_number);
+        let url = set_page_number(page_number, self.url.clone());
+        let req = build_request(Method::Get, url, &self.config, None);
+        let mut res =
+            self.client.send(req).await.map_err(surf_to_tool_error)?;
+        if res.status().is_success() {
+            res.body_json::<Page<T>>().await.map_err(surf_to_tool_error)
+        } else {
+            error!("Failed to retrieve page {} :(", page_number);
+            Err(response_to_tool_error(&mut res).await)
+        }
+    }
+
+    fn next_page(&self, page: &Page<T>, depth: u32) -> Option<u32> {
+        if self.max_depth != 0 && depth >= self.max_depth {
+            return None;
+        }
+        page.meta.as_ref().and_then(|meta| meta.pagination.next_page)
+    }
+
+    /// Stream each page as it is retrieved
+    pub fn pages(self) -> impl Stream<Item = Result<Page<T>, ToolError>> {
+        let start_page = self.start_page;
+        stream::try_unfold(
+            (self, Some(start_page), 0),
+            |(paginator, page_number, depth)| async move {
+                let page_number = match page_number {
+                    Some(n) => n,
+                    None => return Ok(None),
+                };
+                let page = paginator.page(page_number).await?;
+                let depth = depth + 1;
+                let next_page = paginator.next_page(&page, depth);
+                Ok(Some((page, (paginator, next_page, depth))))
+            },
+        )
+    }
+
+    /// Stream each item, retrieving pages as needed
+    pub fn stream(self) -> impl Stream<Item = Result<T, ToolError>> {
+        self.pages()
+            .map_ok(|page| stream::iter(page.data.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Retrieve every page and collect the items
+    pub async fn collect(self) -> Result<Vec<T>, ToolError> {
+        self.stream().try_collect().await
+    }
+
+    /// Retrieve every page and collect the items along with any included
+    /// resources
+    pub async fn collect_with_included(
+        self,
+    ) -> Result<(Vec<T>, Vec<Value>), ToolError> {
+        self.pages()
+            .try_fold(
+                (Vec::new(), Vec::new()),
+                |(mut data, mut included), page| {
+                    data.extend(page.data);
+                    included.extend(page.included);
+                    async move { Ok((data, included)) }
+                },
+            )
+            .await
+    }
+}
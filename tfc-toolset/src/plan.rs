@@ -0,0 +1,265 @@
+use crate::{
+    build_request,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    logs,
+    run::{self, Status},
+    settings::Core,
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, time::Duration};
+use surf::{http::Method, Client, RequestBuilder};
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Attributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_changes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_additions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_changes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_destructions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_imports: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_read_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Plan {
+    pub id: String,
+    pub attributes: Attributes,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct PlanOuter {
+    pub data: Plan,
+}
+
+// Actions Terraform can take on a resource or output
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    NoOp,
+    Create,
+    Read,
+    Update,
+    Delete,
+    Forget,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Change {
+    pub actions: Vec<Action>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub importing: Option<Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ResourceChange {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    pub change: Change,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action_reason: Option<String>,
+}
+
+/// The JSON representation of a plan, as produced by `terraform show -json`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PlanJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terraform_version: Option<String>,
+    #[serde(default)]
+    pub resource_changes: Vec<ResourceChange>,
+    #[serde(default)]
+    pub output_changes: BTreeMap<String, Change>,
+}
+
+/// Counts of the resource changes in a plan
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ChangeSummary {
+    pub add: u32,
+    pub change: u32,
+    pub destroy: u32,
+    pub import: u32,
+}
+
+impl ChangeSummary {
+    pub fn has_changes(&self) -> bool {
+        self.add + self.change + self.destroy + self.import > 0
+    }
+
+    /// Add the counts from another summary to this one
+    pub fn merge(&mut self, other: &ChangeSummary) {
+        self.add += other.add;
+        self.change += other.change;
+        self.destroy += other.destroy;
+        self.import += other.import;
+    }
+}
+
+impl PlanJson {
+    /// Summarize the resource changes the same way `terraform plan` does,
+    /// a replacement counts as both an add and a destroy
+    pub fn summary(&self) -> ChangeSummary {
+        let mut summary = ChangeSummary::default();
+        for resource in &self.resource_changes {
+            let actions = &resource.change.actions;
+            if actions.contains(&Action::Create) {
+                summary.add += 1;
+            }
+            if actions.contains(&Action::Update) {
+                summary.change += 1;
+            }
+            if actions.contains(&Action::Delete) {
+                summary.destroy += 1;
+            }
+            if resource.change.importing.is_some() {
+                summary.import += 1;
+            }
+        }
+        summary
+    }
+}
+
+pub async fn show(
+    plan_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Plan, ToolError> {
+    info!("Retrieving plan {}.", plan_id);
+    let url = Url::parse(&format!("{}/plans/{}", config.base_url, plan_id))?;
+    let req = build_request(Method::Get, url, config, None);
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        info!("Successfully retrieved plan!");
+        let plan: PlanOuter =
+            res.body_json().await.map_err(surf_to_tool_error)?;
+        Ok(plan.data)
+    } else {
+        error!("Failed to retrieve plan :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+pub async fn show_by_run(
+    run_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Plan, ToolError> {
+    let run = run::status(run_id, config, client.clone()).await?;
+    match run.plan_id() {
+        Some(plan_id) => show(&plan_id, config, client).await,
+        None => Err(ToolError::General(anyhow::anyhow!(
+            "Run {} does not have a plan",
+            run_id
+        ))),
+    }
+}
+
+/// Retrieve the JSON representation of a plan
+///
+/// The API responds with a redirect to a temporary download URL, which is
+/// followed without sending the token.
+pub async fn json_output(
+    plan_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<PlanJson, ToolError> {
+    info!("Retrieving JSON output for plan {}.", plan_id);
+    let url = Url::parse(&format!(
+        "{}/plans/{}/json-output",
+        config.base_url, plan_id
+    ))?;
+    let req = build_request(Method::Get, url, config, None);
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_redirection() {
+        if let Some(location) = res.header("Location") {
+            let url = Url::parse(location.as_str())?;
+            let req = RequestBuilder::new(Method::Get, url).build();
+            res = client.send(req).await.map_err(surf_to_tool_error)?;
+        }
+    }
+    if res.status().is_success() {
+        info!("Successfully retrieved plan JSON output!");
+        let plan: PlanJson =
+            res.body_json().await.map_err(surf_to_tool_error)?;
+        Ok(plan)
+    } else {
+        error!("Failed to retrieve plan JSON output :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+/// Summarize the resource changes of a plan from its JSON representation
+pub async fn summary(
+    plan_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<ChangeSummary, ToolError> {
+    Ok(json_output(plan_id, config, client).await?.summary())
+}
+
+/// Retrieve the complete log for a plan
+pub async fn logs(
+    plan_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<String, ToolError> {
+    let plan = show(plan_id, config, client.clone()).await?;
+    match plan.attributes.log_read_url {
+        Some(log_read_url) => logs::read_all(&log_read_url, client).await,
+        None => Ok(String::new()),
+    }
+}
+
+/// Follow the log for a plan until it has finished
+pub async fn follow_logs<C: FnMut(&str)>(
+    plan_id: &str,
+    poll_interval: Duration,
+    config: &Core,
+    client: Client,
+    on_chunk: C,
+) -> Result<(), ToolError> {
+    logs::follow(
+        || {
+            let client = client.clone();
+            async move {
+                let plan = show(plan_id, config, client).await?;
+                Ok((
+                    plan.attributes.log_read_url,
+                    plan.attributes.status.unwrap_or_default(),
+                ))
+            }
+        },
+        poll_interval,
+        client.clone(),
+        on_chunk,
+    )
+    .await
+}
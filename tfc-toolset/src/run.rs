@@ -1,8 +1,9 @@
 use crate::{
     build_request,
-    error::{surf_to_tool_error, ToolError},
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    plan::{self, ChangeSummary},
     settings::Core,
-    workspace, BASE_URL,
+    workspace,
 };
 use async_std::{
     sync::{Arc, Mutex, RwLock},
@@ -72,9 +73,11 @@ pub enum Status {
     PolicySoftFailed,
     Unreachable,
     ForceCanceled,
+    Finished,
+    // Any status not listed above
     #[default]
+    #[serde(other)]
     Unknown,
-    Finished,
 }
 
 impl Display for Status {
@@ -213,9 +216,25 @@ pub struct Workspace {
     pub id: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelationshipData {
+    #[serde(rename = "type")]
+    pub relationship_type: String,
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelationshipOuter {
+    pub data: Option<RelationshipData>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Relationships {
     pub workspace: WorkspaceOuter,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<RelationshipOuter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply: Option<RelationshipOuter>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -229,6 +248,17 @@ pub struct Run {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub links: Option<Links>,
 }
+
+impl Run {
+    pub fn plan_id(&self) -> Option<String> {
+        self.relationships.plan.clone().and_then(|p| p.data).map(|d| d.id)
+    }
+
+    pub fn apply_id(&self) -> Option<String> {
+        self.relationships.apply.clone().and_then(|a| a.data).map(|d| d.id)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct RunOuter {
     pub data: Run,
@@ -248,6 +278,8 @@ impl RunOuter {
                             id: workspace_id.to_string(),
                         },
                     },
+                    plan: None,
+                    apply: None,
                 },
                 links: None,
             },
@@ -268,12 +300,16 @@ pub struct QueueOptions {
     pub max_iterations: usize,
     pub status_check_sleep_seconds: u64,
     pub cancel_on_timeout: bool,
+    #[serde(default)]
+    pub summarize_changes: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct QueueResult {
     pub results: Vec<RunResult>,
     pub errors: Vec<RunResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ChangeSummary>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -282,6 +318,8 @@ pub struct RunResult {
     pub id: RunId,
     pub status: String,
     pub workspace_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ChangeSummary>,
 }
 
 pub type RunId = String;
@@ -293,7 +331,7 @@ pub async fn create(
     client: Client,
 ) -> Result<Run, ToolError> {
     info!("Creating run for workspace: {}", workspace_id);
-    let url = Url::parse(&format!("{}/runs", BASE_URL))?;
+    let url = Url::parse(&format!("{}/runs", config.base_url))?;
     let req = build_request(
         Method::Post,
         url,
@@ -308,10 +346,9 @@ pub async fn create(
                 let run: RunOuter = serde_json::from_str(&res)?;
                 Ok(run.data)
             } else {
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
+                let error = response_to_tool_error(&mut r).await;
                 error!("Failed to create run: {}", error);
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(error)
             }
         }
         Err(e) => Err(surf_to_tool_error(e)),
@@ -324,7 +361,7 @@ pub async fn status(
     client: Client,
 ) -> Result<Run, ToolError> {
     info!("Getting status for run: {}", run_id);
-    let url = Url::parse(&format!("{}/runs/{}", BASE_URL, run_id))?;
+    let url = Url::parse(&format!("{}/runs/{}", config.base_url, run_id))?;
     let req = build_request(Method::Get, url, config, None);
     match client.send(req).await {
         Ok(mut r) => {
@@ -335,9 +372,7 @@ pub async fn status(
                 Ok(run.data)
             } else {
                 error!("Failed to retrieve run status :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(surf_to_tool_error(e)),
@@ -350,8 +385,10 @@ pub async fn cancel(
     client: Client,
 ) -> Result<(), ToolError> {
     info!("Cancelling run: {}", run_id);
-    let url =
-        Url::parse(&format!("{}/runs/{}/actions/cancel", BASE_URL, run_id))?;
+    let url = Url::parse(&format!(
+        "{}/runs/{}/actions/cancel",
+        config.base_url, run_id
+    ))?;
     let req = build_request(Method::Post, url, config, None);
     match client.send(req).await {
         Ok(mut r) => {
@@ -360,9 +397,7 @@ pub async fn cancel(
                 Ok(())
             } else {
                 error!("Failed to cancel run :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(surf_to_tool_error(e)),
@@ -375,8 +410,10 @@ pub async fn discard(
     client: Client,
 ) -> Result<(), ToolError> {
     info!("Discarding run: {}", run_id);
-    let url =
-        Url::parse(&format!("{}/runs/{}/actions/discard", BASE_URL, run_id))?;
+    let url = Url::parse(&format!(
+        "{}/runs/{}/actions/discard",
+        config.base_url, run_id
+    ))?;
     let req = build_request(Method::Post, url, config, None);
     match client.send(req).await {
         Ok(mut r) => {
@@ -385,9 +422,34 @@ pub async fn discard(
                 Ok(())
             } else {
                 error!("Failed to discard run :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
+            }
+        }
+        Err(e) => Err(surf_to_tool_error(e)),
+    }
+}
+
+pub async fn apply(
+    run_id: &str,
+    comment: Option<String>,
+    config: &Core,
+    client: Client,
+) -> Result<(), ToolError> {
+    info!("Applying run: {}", run_id);
+    let url = Url::parse(&format!(
+        "{}/runs/{}/actions/apply",
+        config.base_url, run_id
+    ))?;
+    let body = comment.map(|comment| json!({ "comment": comment }));
+    let req = build_request(Method::Post, url, config, body);
+    match client.send(req).await {
+        Ok(mut r) => {
+            if r.status().is_success() {
+                info!("Successfully applied run!");
+                Ok(())
+            } else {
+                error!("Failed to apply run :(");
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(surf_to_tool_error(e)),
@@ -462,14 +524,24 @@ fn build_handle(
             ))
             .await;
         }
+        let status = run.attributes.status.clone().unwrap_or(Status::Unknown);
+        let mut summary = None;
+        if options.summarize_changes && !ERROR_STATUSES.contains(&status) {
+            if let Some(plan_id) = run.plan_id() {
+                match plan::summary(&plan_id, &core, client.clone()).await {
+                    Ok(s) => summary = Some(s),
+                    Err(e) => error!(
+                        "Failed to summarize plan {} for run {}: {}",
+                        &plan_id, &run_id, e
+                    ),
+                }
+            }
+        }
         Ok(RunResult {
             id: run_id,
-            status: run
-                .attributes
-                .status
-                .unwrap_or(Status::Unknown)
-                .to_string(),
+            status: status.to_string(),
             workspace_id: id,
+            summary,
         })
     })
 }
@@ -539,6 +611,7 @@ pub async fn work_queue(
                                     id: "unknown".to_string(),
                                     status: e.to_string(),
                                     workspace_id: work.id.clone(),
+                                    summary: None,
                                 });
                                 error!(
                                     "Error processing workspace {}: {}",
@@ -562,6 +635,14 @@ pub async fn work_queue(
 
     let return_results = results.lock().await.clone();
     let return_errors = errors.lock().await.clone();
+    let summary = return_results
+        .iter()
+        .filter_map(|result| result.summary.as_ref())
+        .fold(None, |total: Option<ChangeSummary>, summary| {
+            let mut total = total.unwrap_or_default();
+            total.merge(summary);
+            Some(total)
+        });
 
-    Ok(QueueResult { results: return_results, errors: return_errors })
+    Ok(QueueResult { results: return_results, errors: return_errors, summary })
 }
@@ -1,9 +1,28 @@
-use crate::error::ToolError;
-use config::{Config, ConfigError, Environment, File};
+use crate::{
+    credentials::{self, TokenSource},
+    error::ToolError,
+    filter::expr::Expr,
+    run::Status,
+    workspace::Workspace,
+    BASE_URL,
+};
+use config::{
+    builder::DefaultState, Config, ConfigBuilder, ConfigError, Environment,
+    File, FileFormat, FileSourceFile, Map, Source, Value,
+};
+use regex::Regex;
+use semver::Version;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+    str::FromStr,
+};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
 
 pub const DEFAULT_TERRAFORM_VERSION: &str = "1.5.7";
+pub const PROFILE_ENV: &str = "TFCT_PROFILE";
+pub const CONFIG_ENV: &str = "TFCT_CONFIG";
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[non_exhaustive]
@@ -12,6 +31,14 @@ pub enum Operators {
     NotEquals,
     Contains,
     NotContains,
+    Matches,
+    NotMatches,
+    Glob,
+    NotGlob,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
 }
 
 impl FromStr for Operators {
@@ -23,51 +50,496 @@ impl FromStr for Operators {
             "!=" => Ok(Operators::NotEquals),
             "~=" => Ok(Operators::Contains),
             "!~=" => Ok(Operators::NotContains),
+            "=~" => Ok(Operators::Matches),
+            "!=~" => Ok(Operators::NotMatches),
+            "*=" => Ok(Operators::Glob),
+            "!*=" => Ok(Operators::NotGlob),
+            "<" => Ok(Operators::LessThan),
+            "<=" => Ok(Operators::LessThanOrEqual),
+            ">" => Ok(Operators::GreaterThan),
+            ">=" => Ok(Operators::GreaterThanOrEqual),
             _ => Err(ToolError::InvalidQueryOperator(s.to_string())),
         }
     }
 }
 
+impl Display for Operators {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let token = match self {
+            Operators::Equals => "==",
+            Operators::NotEquals => "!=",
+            Operators::Contains => "~=",
+            Operators::NotContains => "!~=",
+            Operators::Matches => "=~",
+            Operators::NotMatches => "!=~",
+            Operators::Glob => "*=",
+            Operators::NotGlob => "!*=",
+            Operators::LessThan => "<",
+            Operators::LessThanOrEqual => "<=",
+            Operators::GreaterThan => ">",
+            Operators::GreaterThanOrEqual => ">=",
+        };
+        write!(f, "{}", token)
+    }
+}
+
+impl Operators {
+    /// Whether a match excludes the workspace rather than being required
+    pub fn is_negated(&self) -> bool {
+        matches!(
+            self,
+            Operators::NotEquals
+                | Operators::NotContains
+                | Operators::NotMatches
+                | Operators::NotGlob
+        )
+    }
+
+    // Compile the pattern for the operators that need one
+    pub(crate) fn compile(
+        &self,
+        pattern: &str,
+    ) -> Result<Option<Pattern>, ToolError> {
+        let regex = match self {
+            Operators::Matches | Operators::NotMatches => Regex::new(pattern),
+            Operators::Glob | Operators::NotGlob => {
+                Regex::new(&glob_to_regex(pattern))
+            }
+            Operators::LessThan
+            | Operators::LessThanOrEqual
+            | Operators::GreaterThan
+            | Operators::GreaterThanOrEqual => {
+                return match parse_version(pattern) {
+                    Some(version) => Ok(Some(Pattern::Version(version))),
+                    None => {
+                        Err(ToolError::InvalidQueryVersion(pattern.to_string()))
+                    }
+                }
+            }
+            _ => return Ok(None),
+        };
+        regex.map(|r| Some(Pattern::Regex(r))).map_err(|source| {
+            ToolError::InvalidQueryPattern {
+                pattern: pattern.to_string(),
+                source,
+            }
+        })
+    }
+
+    // Compile an age such as `90d` for the comparison operators
+    pub(crate) fn compile_age(&self, age: &str) -> Result<Pattern, ToolError> {
+        match self {
+            Operators::LessThan
+            | Operators::LessThanOrEqual
+            | Operators::GreaterThan
+            | Operators::GreaterThanOrEqual => parse_age(age)
+                .map(Pattern::Age)
+                .ok_or_else(|| ToolError::InvalidQueryAge(age.to_string())),
+            _ => Err(ToolError::InvalidQueryAge(format!("{} {}", self, age))),
+        }
+    }
+
+    fn compare<T: PartialOrd>(&self, value: T, pattern: T) -> bool {
+        match self {
+            Operators::LessThan => value < pattern,
+            Operators::LessThanOrEqual => value <= pattern,
+            Operators::GreaterThan => value > pattern,
+            Operators::GreaterThanOrEqual => value >= pattern,
+            _ => false,
+        }
+    }
+
+    // Whether the value matches the pattern, ignoring negation
+    pub(crate) fn is_match(
+        &self,
+        pattern: &str,
+        compiled: Option<&Pattern>,
+        value: &str,
+    ) -> bool {
+        match (self, compiled) {
+            (Operators::Equals | Operators::NotEquals, _) => value == pattern,
+            (Operators::Contains | Operators::NotContains, _) => {
+                value.contains(pattern)
+            }
+            (_, Some(Pattern::Regex(regex))) => regex.is_match(value),
+            (_, Some(Pattern::Version(version))) => {
+                // Values that are not versions, such as `latest`, never match
+                match parse_version(value) {
+                    Some(value) => self.compare(&value, version),
+                    None => false,
+                }
+            }
+            (_, Some(Pattern::Age(age))) => {
+                match OffsetDateTime::parse(value, &Rfc3339) {
+                    Ok(timestamp) => self
+                        .compare(OffsetDateTime::now_utc() - timestamp, *age),
+                    Err(_) => false,
+                }
+            }
+            (_, None) => false,
+        }
+    }
+}
+
+/// A query value compiled when the query is built, for the operators that
+/// need more than a string comparison
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Regex(Regex),
+    Version(Version),
+    Age(Duration),
+}
+
+// Parse an age made up of a number and a unit of `m`, `h`, `d` or `w`, e.g.
+// `90d`
+fn parse_age(age: &str) -> Option<Duration> {
+    let age = age.trim();
+    let unit = age.chars().last()?;
+    let amount = age[..age.len() - unit.len_utf8()].parse::<u32>().ok()?;
+    let amount = i64::from(amount);
+    match unit {
+        'm' => Some(Duration::minutes(amount)),
+        'h' => Some(Duration::hours(amount)),
+        'd' => Some(Duration::days(amount)),
+        'w' => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+// Parse a version leniently so that `1.5` and `v1` are read as `1.5.0` and
+// `1.0.0`
+fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim().trim_start_matches('v');
+    let (core, rest) = match value.find(['-', '+']) {
+        Some(i) => value.split_at(i),
+        None => (value, ""),
+    };
+    let version = match core.split('.').count() {
+        1 => format!("{}.0.0{}", core, rest),
+        2 => format!("{}.0{}", core, rest),
+        _ => value.to_string(),
+    };
+    Version::parse(&version).ok()
+}
+
+// Translate a glob into an anchored regex, `*` matches any run of characters,
+// `?` a single character and `[...]` a character class
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if matches!(c, '\\' | '[' | '&' | '~') {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct VariableQuery {
+    key: String,
+    operator: Operators,
+    value: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "VariableQuery")]
 pub struct Variable {
     pub key: String,
     pub operator: Operators,
     pub value: String,
+    #[serde(skip)]
+    pattern: Option<Pattern>,
+}
+
+impl Variable {
+    /// Build a variable query, compiling the value if the operator needs it
+    pub fn new(
+        key: &str,
+        operator: Operators,
+        value: &str,
+    ) -> Result<Self, ToolError> {
+        let pattern = operator.compile(value)?;
+        Ok(Variable {
+            key: key.to_string(),
+            operator,
+            value: value.to_string(),
+            pattern,
+        })
+    }
+
+    /// Whether a variable value matches the query, ignoring negation
+    pub fn is_match(&self, value: &str) -> bool {
+        self.operator.is_match(&self.value, self.pattern.as_ref(), value)
+    }
+}
+
+impl TryFrom<VariableQuery> for Variable {
+    type Error = ToolError;
+
+    fn try_from(query: VariableQuery) -> Result<Self, Self::Error> {
+        Variable::new(&query.key, query.operator, &query.value)
+    }
 }
 
 impl FromStr for Variable {
     type Err = ToolError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(':').collect();
+        // The value is last so it is free to contain colons
+        let parts: Vec<&str> = s.splitn(3, ':').collect();
         if parts.len() != 3 {
             return Err(ToolError::InvalidVariableQuery(s.to_string()));
         }
         let operator = Operators::from_str(parts[1])?;
-        Ok(Variable {
-            key: parts[0].to_string(),
-            operator,
-            value: parts[2].to_string(),
-        })
+        Variable::new(parts[0], operator, parts[2])
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+struct TagQuery {
+    operator: Operators,
+    name: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "TagQuery")]
 pub struct Tag {
     pub operator: Operators,
     pub name: String,
+    #[serde(skip)]
+    pattern: Option<Pattern>,
+}
+
+impl Tag {
+    /// Build a tag query, compiling the name if the operator needs it
+    pub fn new(operator: Operators, name: &str) -> Result<Self, ToolError> {
+        let pattern = operator.compile(name)?;
+        Ok(Tag { operator, name: name.to_string(), pattern })
+    }
+
+    /// Whether a tag name matches the query, ignoring negation
+    pub fn is_match(&self, name: &str) -> bool {
+        self.operator.is_match(&self.name, self.pattern.as_ref(), name)
+    }
+}
+
+impl TryFrom<TagQuery> for Tag {
+    type Error = ToolError;
+
+    fn try_from(query: TagQuery) -> Result<Self, Self::Error> {
+        Tag::new(query.operator, &query.name)
+    }
 }
 
 impl FromStr for Tag {
     type Err = ToolError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(':').collect();
+        // Tag names commonly contain colons, e.g. team:infra
+        let parts: Vec<&str> = s.splitn(2, ':').collect();
         if parts.len() != 2 {
             return Err(ToolError::InvalidTagQuery(s.to_string()));
         }
         let operator = Operators::from_str(parts[0])?;
-        Ok(Tag { operator, name: parts[1].to_string() })
+        Tag::new(operator, parts[1])
+    }
+}
+
+/// A workspace attribute that can be queried
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum WorkspaceAttribute {
+    Name,
+    ExecutionMode,
+    TerraformVersion,
+    AutoApply,
+    #[serde(rename = "vcs_repo.identifier")]
+    VcsRepoIdentifier,
+    WorkingDirectory,
+    AgentPoolId,
+    HasVcsRepo,
+    #[serde(rename = "latest_run.status")]
+    LatestRunStatus,
+    #[serde(rename = "latest_run.age")]
+    LatestRunAge,
+    #[serde(rename = "updated_at.age")]
+    UpdatedAge,
+}
+
+impl FromStr for WorkspaceAttribute {
+    type Err = ToolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(WorkspaceAttribute::Name),
+            "execution_mode" => Ok(WorkspaceAttribute::ExecutionMode),
+            "terraform_version" => Ok(WorkspaceAttribute::TerraformVersion),
+            "auto_apply" => Ok(WorkspaceAttribute::AutoApply),
+            "vcs_repo.identifier" => Ok(WorkspaceAttribute::VcsRepoIdentifier),
+            "working_directory" => Ok(WorkspaceAttribute::WorkingDirectory),
+            "agent_pool_id" => Ok(WorkspaceAttribute::AgentPoolId),
+            "has_vcs_repo" => Ok(WorkspaceAttribute::HasVcsRepo),
+            "latest_run.status" => Ok(WorkspaceAttribute::LatestRunStatus),
+            "latest_run.age" => Ok(WorkspaceAttribute::LatestRunAge),
+            "updated_at.age" => Ok(WorkspaceAttribute::UpdatedAge),
+            _ => Err(ToolError::InvalidQueryAttribute(s.to_string())),
+        }
+    }
+}
+
+impl Display for WorkspaceAttribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WorkspaceAttribute::Name => "name",
+            WorkspaceAttribute::ExecutionMode => "execution_mode",
+            WorkspaceAttribute::TerraformVersion => "terraform_version",
+            WorkspaceAttribute::AutoApply => "auto_apply",
+            WorkspaceAttribute::VcsRepoIdentifier => "vcs_repo.identifier",
+            WorkspaceAttribute::WorkingDirectory => "working_directory",
+            WorkspaceAttribute::AgentPoolId => "agent_pool_id",
+            WorkspaceAttribute::HasVcsRepo => "has_vcs_repo",
+            WorkspaceAttribute::LatestRunStatus => "latest_run.status",
+            WorkspaceAttribute::LatestRunAge => "latest_run.age",
+            WorkspaceAttribute::UpdatedAge => "updated_at.age",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl WorkspaceAttribute {
+    /// Whether the attribute is a timestamp compared by its age
+    pub fn is_age(&self) -> bool {
+        matches!(
+            self,
+            WorkspaceAttribute::LatestRunAge | WorkspaceAttribute::UpdatedAge
+        )
+    }
+
+    /// The value of the attribute as a string, if it is set
+    pub fn value(&self, workspace: &Workspace) -> Option<String> {
+        let attributes = &workspace.attributes;
+        match self {
+            WorkspaceAttribute::Name => attributes.name.clone(),
+            WorkspaceAttribute::ExecutionMode => {
+                attributes.execution_mode.as_ref().map(|m| m.to_string())
+            }
+            WorkspaceAttribute::TerraformVersion => {
+                attributes.terraform_version.clone()
+            }
+            WorkspaceAttribute::AutoApply => {
+                attributes.auto_apply.map(|a| a.to_string())
+            }
+            WorkspaceAttribute::VcsRepoIdentifier => attributes
+                .vcs_repo
+                .as_ref()
+                .and_then(|repo| repo.identifier.clone()),
+            WorkspaceAttribute::WorkingDirectory => {
+                attributes.working_directory.clone()
+            }
+            WorkspaceAttribute::AgentPoolId => attributes.agent_pool_id.clone(),
+            WorkspaceAttribute::HasVcsRepo => {
+                Some(attributes.vcs_repo.is_some().to_string())
+            }
+            WorkspaceAttribute::LatestRunStatus => workspace
+                .latest_run
+                .as_ref()
+                .and_then(|run| run.attributes.status.as_ref())
+                .map(|status| status.to_string()),
+            WorkspaceAttribute::LatestRunAge => workspace
+                .latest_run
+                .as_ref()
+                .and_then(|run| run.attributes.created_at.clone()),
+            WorkspaceAttribute::UpdatedAge => attributes.updated_at.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AttributeQuery {
+    attribute: WorkspaceAttribute,
+    operator: Operators,
+    value: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "AttributeQuery")]
+pub struct Attribute {
+    pub attribute: WorkspaceAttribute,
+    pub operator: Operators,
+    pub value: String,
+    #[serde(skip)]
+    pattern: Option<Pattern>,
+}
+
+impl Attribute {
+    /// Build an attribute query, compiling the value if the operator needs it
+    pub fn new(
+        attribute: WorkspaceAttribute,
+        operator: Operators,
+        value: &str,
+    ) -> Result<Self, ToolError> {
+        let pattern = if attribute.is_age() {
+            Some(operator.compile_age(value)?)
+        } else {
+            if attribute == WorkspaceAttribute::LatestRunStatus
+                && matches!(operator, Operators::Equals | Operators::NotEquals)
+                && Status::from(value.to_string()) == Status::Unknown
+                && value != "unknown"
+            {
+                return Err(ToolError::InvalidRunStatus(value.to_string()));
+            }
+            operator.compile(value)?
+        };
+        Ok(Attribute { attribute, operator, value: value.to_string(), pattern })
+    }
+
+    /// Whether an attribute value matches the query, ignoring negation
+    pub fn is_match(&self, value: &str) -> bool {
+        self.operator.is_match(&self.value, self.pattern.as_ref(), value)
+    }
+}
+
+impl TryFrom<AttributeQuery> for Attribute {
+    type Error = ToolError;
+
+    fn try_from(query: AttributeQuery) -> Result<Self, Self::Error> {
+        Attribute::new(query.attribute, query.operator, &query.value)
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = ToolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.splitn(3, ':').collect();
+        if parts.len() != 3 {
+            return Err(ToolError::InvalidAttributeQuery(s.to_string()));
+        }
+        let attribute = WorkspaceAttribute::from_str(parts[0])?;
+        let operator = Operators::from_str(parts[1])?;
+        Attribute::new(attribute, operator, parts[2])
     }
 }
 
@@ -78,37 +550,255 @@ pub struct Pagination {
     pub page_size: String,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RateLimit {
+    /// The maximum number of requests to send per second
+    pub requests_per_second: u32,
+    /// The maximum number of times a throttled or failed request is retried
+    pub max_retries: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self { requests_per_second: 30, max_retries: 10 }
+    }
+}
+
+/// How the HTTP cache is used when making requests
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheMode {
+    /// Use cached responses while they are fresh, revalidating stale ones
+    #[default]
+    Default,
+    /// Never read from or write to the cache
+    NoStore,
+    /// Always make the request, then update the cache with the response
+    Reload,
+    /// Always revalidate cached responses with the API
+    NoCache,
+    /// Use any cached response regardless of its age
+    ForceCache,
+    /// Only use cached responses, failing when there is none, for offline use
+    OnlyIfCached,
+}
+
+impl FromStr for CacheMode {
+    type Err = ToolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(CacheMode::Default),
+            "no-store" => Ok(CacheMode::NoStore),
+            "reload" => Ok(CacheMode::Reload),
+            "no-cache" => Ok(CacheMode::NoCache),
+            "force-cache" => Ok(CacheMode::ForceCache),
+            "only-if-cached" => Ok(CacheMode::OnlyIfCached),
+            _ => Err(ToolError::InvalidCacheMode(s.to_string())),
+        }
+    }
+}
+
+impl Display for CacheMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mode = match self {
+            CacheMode::Default => "default",
+            CacheMode::NoStore => "no-store",
+            CacheMode::Reload => "reload",
+            CacheMode::NoCache => "no-cache",
+            CacheMode::ForceCache => "force-cache",
+            CacheMode::OnlyIfCached => "only-if-cached",
+        };
+        write!(f, "{}", mode)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Cache {
+    pub mode: CacheMode,
+    /// The fraction of the time since a response was last modified that it
+    /// is considered fresh for when the API does not say, `0` disables this
+    pub ttl_heuristic: f32,
+    /// The directory to store cached responses in, defaults to a
+    /// `tfc-toolset` directory in the user cache directory
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self { mode: CacheMode::Default, ttl_heuristic: 0.0, dir: None }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Query {
     pub name: Option<String>,
     pub wildcard_name: Option<String>,
     pub variables: Option<Vec<Variable>>,
     pub tags: Option<Vec<Tag>>,
+    pub attributes: Option<Vec<Attribute>>,
+    pub expr: Option<Expr>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Workspaces {
     pub query: Option<Query>,
+    /// Relationships to side-load when retrieving workspaces, e.g. `vars`
+    pub include: Option<Vec<String>>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// A named set of settings under `[profiles.<name>]`, layered over the rest
+/// of the settings file when selected
+#[derive(Clone, Debug)]
+struct Profile(Map<String, Value>);
+
+impl Source for Profile {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
+// A profile that points at another host or brings its own way of getting a
+// token must not inherit the top level token, which would otherwise be sent
+// to that host and take precedence over the profile's token command
+fn profile_table(mut table: Map<String, Value>) -> Map<String, Value> {
+    if ["base_url", "token_command"].iter().any(|k| table.contains_key(*k)) {
+        table.entry("token".to_string()).or_insert_with(|| "".into());
+    }
+    table
+}
+
+/// Where settings are loaded from beyond the default locations
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    /// A settings file that takes precedence over every other file
+    pub config: Option<PathBuf>,
+    /// The profile to select, see `load`
+    pub profile: Option<String>,
+}
+
+/// The user settings file, `$XDG_CONFIG_HOME/tfct/config.toml` falling back
+/// to `~/.config/tfct/config.toml`
+pub fn user_config_file() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("tfct").join("config.toml"))
+}
+
+// The settings files to read from lowest to highest precedence, files that
+// were asked for explicitly must exist
+fn config_files(
+    options: &LoadOptions,
+) -> Vec<File<FileSourceFile, FileFormat>> {
+    let mut files = Vec::new();
+    if let Some(path) = user_config_file() {
+        files.push(File::from(path).required(false));
+    }
+    files.push(File::with_name("settings.toml").required(false));
+    if let Some(path) =
+        std::env::var_os(CONFIG_ENV).filter(|path| !path.is_empty())
+    {
+        files.push(File::from(PathBuf::from(path)).required(true));
+    }
+    if let Some(path) = &options.config {
+        files.push(File::from(path.clone()).required(true));
+    }
+    files
+}
+
+/// Load the settings files and environment on top of the given defaults
+///
+/// Settings files are merged so that each one takes precedence over the
+/// next: the `config` option, `$TFCT_CONFIG`, `./settings.toml` and then
+/// `user_config_file`.
+///
+/// When a profile is given, or otherwise selected by the `TFCT_PROFILE`
+/// environment variable or the `profile` key of the settings files, its
+/// settings take precedence over the rest of the files but not over the
+/// environment. A profile that sets `base_url` or `token_command` without a
+/// `token` does not inherit the token of the files.
+pub fn load(
+    defaults: ConfigBuilder<DefaultState>,
+    options: &LoadOptions,
+) -> Result<Config, ConfigError> {
+    let files = config_files(options);
+    // Read the files on their own first to find the profile
+    let base = Config::builder().add_source(files.clone()).build()?;
+    let profile = options
+        .profile
+        .clone()
+        .or_else(|| std::env::var(PROFILE_ENV).ok())
+        .or_else(|| base.get_string("profile").ok())
+        .filter(|p| !p.is_empty());
+    let mut builder = defaults.add_source(files);
+    if let Some(name) = profile {
+        let table = base
+            .get_table(&format!("profiles.{}", name.to_lowercase()))
+            .map_err(|_| {
+                let available = base
+                    .get_table("profiles")
+                    .map(|profiles| {
+                        profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                    })
+                    .unwrap_or_default();
+                ConfigError::Message(format!(
+                    "Profile {} not found, available profiles: [{}]",
+                    name, available
+                ))
+            })?;
+        builder = builder
+            .add_source(Profile(profile_table(table)))
+            .set_override("profile", name)?;
+    }
+    builder
+        // Add in settings from the environment
+        // Eg.. `DEBUG=1 ./target/app` would set the `debug` key
+        .add_source(Environment::default())
+        .build()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Core {
+    /// The profile the settings were loaded with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     pub log: String,
+    pub base_url: String,
     pub token: String,
+    /// A command that outputs the token, run when `token` is not set
+    pub token_command: Option<String>,
+    /// Where the token was found, set by `resolve_token`
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub token_source: Option<TokenSource>,
     pub org: String,
     pub project: Option<String>,
     pub output: PathBuf,
     pub save_output: bool,
     pub pagination: Pagination,
+    pub batch_concurrency: usize,
+    pub rate_limit: RateLimit,
+    pub cache: Cache,
     pub workspaces: Workspaces,
     pub terraform_version: String,
 }
 
 impl Core {
     pub fn new() -> Result<Self, ConfigError> {
-        let s = Config::builder()
+        Self::with_options(&LoadOptions::default())
+    }
+
+    /// Load the settings from an extra file or with a profile, see `load`
+    pub fn with_options(options: &LoadOptions) -> Result<Self, ConfigError> {
+        let defaults = Config::builder()
             // Set defaults
             .set_default("log", "info".to_string())?
+            .set_default("base_url", BASE_URL.to_string())?
             .set_default("token", "".to_string())?
             .set_default("org", "".to_string())?
             .set_default("output", "report.json".to_string())?
@@ -116,17 +806,501 @@ impl Core {
             .set_default("pagination.start_page", "1".to_string())?
             .set_default("pagination.max_depth", "1".to_string())?
             .set_default("pagination.page_size", "20".to_string())?
+            .set_default("batch_concurrency", 10)?
+            .set_default("rate_limit.requests_per_second", 30)?
+            .set_default("rate_limit.max_retries", 10)?
+            .set_default("cache.mode", "default".to_string())?
+            .set_default("cache.ttl_heuristic", 0.0)?
             .set_default(
                 "terraform_version",
                 DEFAULT_TERRAFORM_VERSION.to_string(),
             )?
-            .set_default("workspaces.query", None::<String>)?
-            // Start off by merging in the "default" configuration file
-            .add_source(File::with_name("settings.toml").required(false))
-            // Add in settings from the environment
-            // Eg.. `DEBUG=1 ./target/app` would set the `debug` key
-            .add_source(Environment::default())
-            .build()?;
-        s.try_deserialize()
+            .set_default("workspaces.query", None::<String>)?;
+        let mut core: Self = load(defaults, options)?.try_deserialize()?;
+        let base_url = core.base_url.clone();
+        core.set_base_url(&base_url);
+        Ok(core)
+    }
+
+    /// Set the base URL, without a trailing slash so paths can be appended
+    pub fn set_base_url(&mut self, base_url: &str) {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+    }
+
+    /// Fill in the token when it has not been set directly, from the token
+    /// command, a `TF_TOKEN_<host>` environment variable or the Terraform
+    /// credentials file in that order
+    ///
+    /// Call this after applying any overrides, since the host is taken from
+    /// `base_url` and the token command is only run when it is needed.
+    pub fn resolve_token(&mut self) -> Result<(), ToolError> {
+        if !self.token.is_empty() {
+            self.token_source = Some(TokenSource::Config);
+            return Ok(());
+        }
+        if let Some((token, source)) = credentials::resolve(self)? {
+            self.token = token;
+            self.token_source = Some(source);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        ffi::{OsStr, OsString},
+        fs,
+        sync::{Mutex, MutexGuard},
+    };
+
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    // The environment and working directory are shared by every test in the
+    // process, so a test that changes them holds the lock until the guard
+    // puts them back
+    struct ScopedEnv {
+        dir: PathBuf,
+        cwd: PathBuf,
+        vars: Vec<(&'static str, Option<OsString>)>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl ScopedEnv {
+        // Run from a new temporary directory, with the user settings file
+        // inside it and none of the settings set in the environment
+        fn new(name: &str) -> Self {
+            let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let dir = std::env::temp_dir().join(format!(
+                "tfc-toolset-settings-{}-{}",
+                std::process::id(),
+                name
+            ));
+            fs::create_dir_all(dir.join("xdg").join("tfct")).unwrap();
+            let cwd = std::env::current_dir().unwrap();
+            std::env::set_current_dir(&dir).unwrap();
+            let mut env = Self { dir, cwd, vars: vec![], _lock: lock };
+            for key in [CONFIG_ENV, PROFILE_ENV, "ORG", "TOKEN", "BASE_URL"] {
+                env.remove(key);
+            }
+            env.set("XDG_CONFIG_HOME", env.dir.join("xdg"));
+            env
+        }
+
+        fn set(&mut self, key: &'static str, value: impl AsRef<OsStr>) {
+            self.save(key);
+            std::env::set_var(key, value);
+        }
+
+        fn remove(&mut self, key: &'static str) {
+            self.save(key);
+            std::env::remove_var(key);
+        }
+
+        fn save(&mut self, key: &'static str) {
+            if !self.vars.iter().any(|(k, _)| *k == key) {
+                self.vars.push((key, std::env::var_os(key)));
+            }
+        }
+
+        fn write(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.dir.join(path);
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for ScopedEnv {
+        fn drop(&mut self) {
+            for (key, value) in self.vars.drain(..) {
+                match value {
+                    Some(value) => std::env::set_var(key, value),
+                    None => std::env::remove_var(key),
+                }
+            }
+            let _ = std::env::set_current_dir(&self.cwd);
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn load_with(options: &LoadOptions) -> Result<Config, ConfigError> {
+        load(Config::builder().set_default("token", "").unwrap(), options)
+    }
+
+    fn profile(name: &str) -> LoadOptions {
+        LoadOptions { profile: Some(name.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn operators_from_str() {
+        let cases = [
+            ("==", Operators::Equals),
+            ("!=", Operators::NotEquals),
+            ("~=", Operators::Contains),
+            ("!~=", Operators::NotContains),
+            ("=~", Operators::Matches),
+            ("!=~", Operators::NotMatches),
+            ("*=", Operators::Glob),
+            ("!*=", Operators::NotGlob),
+            ("<", Operators::LessThan),
+            ("<=", Operators::LessThanOrEqual),
+            (">", Operators::GreaterThan),
+            (">=", Operators::GreaterThanOrEqual),
+        ];
+        for (token, operator) in cases {
+            let parsed = Operators::from_str(token).unwrap();
+            assert_eq!(parsed.to_string(), operator.to_string());
+            assert_eq!(parsed.to_string(), token);
+        }
+        for invalid in ["", "=", "===", "=<", "~", "contains", " =="] {
+            assert!(matches!(
+                Operators::from_str(invalid),
+                Err(ToolError::InvalidQueryOperator(s)) if s == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn negated_operators() {
+        let negated = ["!=", "!~=", "!=~", "!*="];
+        for token in ["==", "~=", "=~", "*=", "<", "<=", ">", ">="]
+            .iter()
+            .chain(&negated)
+        {
+            let operator = Operators::from_str(token).unwrap();
+            assert_eq!(
+                operator.is_negated(),
+                negated.contains(token),
+                "{}",
+                token
+            );
+        }
+    }
+
+    #[test]
+    fn glob_to_regex_translation() {
+        let cases = [
+            ("*", "^.*$"),
+            ("app-*", "^app\\-.*$"),
+            ("?", "^.$"),
+            ("[abc]", "^[abc]$"),
+            ("[!abc]", "^[^abc]$"),
+            ("[a-z]*", "^[a-z].*$"),
+            ("a.b", "^a\\.b$"),
+            ("a+b(c)|d", "^a\\+b\\(c\\)\\|d$"),
+            ("^start$", "^\\^start\\$$"),
+            ("{x}", "^\\{x\\}$"),
+            ("back\\slash", "^back\\\\slash$"),
+            ("[\\&~]", "^[\\\\\\&\\~]$"),
+        ];
+        for (glob, regex) in cases {
+            assert_eq!(glob_to_regex(glob), regex, "glob {:?}", glob);
+            assert!(
+                Regex::new(&glob_to_regex(glob)).is_ok(),
+                "glob {:?}",
+                glob
+            );
+        }
+    }
+
+    #[test]
+    fn glob_matching() {
+        let cases = [
+            ("*-prod", "app-prod", true),
+            ("*-prod", "app-prod-2", false),
+            ("app-?", "app-1", true),
+            ("app-?", "app-10", false),
+            ("app-[0-9]", "app-7", true),
+            ("app-[!0-9]", "app-7", false),
+            ("app-[!0-9]", "app-x", true),
+            // Regex metacharacters are matched literally
+            ("a.b", "a.b", true),
+            ("a.b", "axb", false),
+            ("a+b", "a+b", true),
+            ("a+b", "aab", false),
+            ("(prod)", "(prod)", true),
+            ("(prod)", "prod", false),
+            ("x|y", "x", false),
+            ("$HOME", "$HOME", true),
+            ("", "", true),
+            ("", "a", false),
+        ];
+        for (glob, value, expected) in cases {
+            let regex = Regex::new(&glob_to_regex(glob)).unwrap();
+            assert_eq!(
+                regex.is_match(value),
+                expected,
+                "{:?} {:?}",
+                glob,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn parse_versions() {
+        let cases = [
+            ("1.5.7", "1.5.7"),
+            ("1.5", "1.5.0"),
+            ("1", "1.0.0"),
+            ("v1", "1.0.0"),
+            ("v1.6", "1.6.0"),
+            (" 1.5.7 ", "1.5.7"),
+            ("1.6.0-beta1", "1.6.0-beta1"),
+            ("1.6-rc1", "1.6.0-rc1"),
+            ("1.6+build.5", "1.6.0+build.5"),
+        ];
+        for (value, version) in cases {
+            assert_eq!(
+                parse_version(value),
+                Some(Version::parse(version).unwrap()),
+                "version {:?}",
+                value
+            );
+        }
+        for invalid in [
+            "", "v", "latest", "~> 1.5", "1.x", "1..5", "1.5.7.1", "1.5.a",
+            "-1",
+        ] {
+            assert_eq!(parse_version(invalid), None, "version {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn parse_ages() {
+        let cases = [
+            ("30m", Duration::minutes(30)),
+            ("12h", Duration::hours(12)),
+            ("90d", Duration::days(90)),
+            ("2w", Duration::weeks(2)),
+            ("0d", Duration::ZERO),
+            (" 7d ", Duration::days(7)),
+        ];
+        for (age, duration) in cases {
+            assert_eq!(parse_age(age), Some(duration), "age {:?}", age);
+        }
+        for invalid in [
+            "", "d", "30", "30s", "30y", "1.5d", "-5d", "d30", "30 d", "3é",
+            "ten d",
+        ] {
+            assert_eq!(parse_age(invalid), None, "age {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn compile_rejects_invalid_patterns() {
+        assert!(matches!(
+            Operators::Matches.compile("("),
+            Err(ToolError::InvalidQueryPattern { .. })
+        ));
+        assert!(matches!(
+            Operators::LessThan.compile("latest"),
+            Err(ToolError::InvalidQueryVersion(_))
+        ));
+        assert!(matches!(
+            Operators::GreaterThan.compile_age("90"),
+            Err(ToolError::InvalidQueryAge(_))
+        ));
+        assert!(matches!(
+            Operators::Equals.compile_age("90d"),
+            Err(ToolError::InvalidQueryAge(_))
+        ));
+        assert!(Operators::Equals.compile("(").unwrap().is_none());
+    }
+
+    #[test]
+    fn version_comparisons() {
+        let cases = [
+            ("<", "1.5", "1.4.9", true),
+            ("<", "1.5", "1.5.0", false),
+            ("<=", "1.5", "1.5.0", true),
+            (">", "1.5", "1.10.0", true),
+            (">=", "v1.5.7", "1.5.7", true),
+            (">", "1.5", "latest", false),
+            ("<", "1.6", "1.6.0-beta1", true),
+        ];
+        for (token, pattern, value, expected) in cases {
+            let operator = Operators::from_str(token).unwrap();
+            let compiled = operator.compile(pattern).unwrap();
+            assert_eq!(
+                operator.is_match(pattern, compiled.as_ref(), value),
+                expected,
+                "{} {} {}",
+                value,
+                token,
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn age_comparisons() {
+        let timestamp = |age: Duration| {
+            (OffsetDateTime::now_utc() - age).format(&Rfc3339).unwrap()
+        };
+        let older = Operators::GreaterThan;
+        let newer = Operators::LessThan;
+        let compiled = older.compile_age("30d").unwrap();
+        assert!(older.is_match(
+            "30d",
+            Some(&compiled),
+            &timestamp(Duration::days(31))
+        ));
+        assert!(!older.is_match(
+            "30d",
+            Some(&compiled),
+            &timestamp(Duration::days(29))
+        ));
+        let compiled = newer.compile_age("12h").unwrap();
+        assert!(newer.is_match(
+            "12h",
+            Some(&compiled),
+            &timestamp(Duration::hours(1))
+        ));
+        assert!(!newer.is_match("12h", Some(&compiled), "not a timestamp"));
+    }
+
+    const PROFILES: &str = r#"
+org = "base-org"
+token = "base-token"
+
+[profiles.dev]
+org = "dev-org"
+
+[profiles.tfe]
+base_url = "https://tfe.example.com/api/v2"
+
+[profiles.vault]
+token_command = "vault read -field=token secret/tfe"
+
+[profiles.own]
+base_url = "https://tfe.example.com/api/v2"
+token = "own-token"
+"#;
+
+    #[test]
+    fn profile_selection() {
+        let mut env = ScopedEnv::new("profile-selection");
+        env.write("settings.toml", PROFILES);
+        let config = load_with(&LoadOptions::default()).unwrap();
+        assert_eq!(config.get_string("org").unwrap(), "base-org");
+        assert!(config.get_string("profile").is_err());
+        let config = load_with(&profile("dev")).unwrap();
+        assert_eq!(config.get_string("org").unwrap(), "dev-org");
+        assert_eq!(config.get_string("profile").unwrap(), "dev");
+        // The environment selects a profile, but the option comes first
+        env.set(PROFILE_ENV, "tfe");
+        let config = load_with(&LoadOptions::default()).unwrap();
+        assert_eq!(config.get_string("profile").unwrap(), "tfe");
+        let config = load_with(&profile("dev")).unwrap();
+        assert_eq!(config.get_string("profile").unwrap(), "dev");
+        // And the files select one when neither does
+        env.remove(PROFILE_ENV);
+        env.write("settings.toml", &format!("profile = \"dev\"\n{}", PROFILES));
+        let config = load_with(&LoadOptions::default()).unwrap();
+        assert_eq!(config.get_string("org").unwrap(), "dev-org");
+    }
+
+    #[test]
+    fn profile_token_inheritance() {
+        let env = ScopedEnv::new("profile-token");
+        env.write("settings.toml", PROFILES);
+        let token = |name: &str| {
+            load_with(&profile(name)).unwrap().get_string("token").unwrap()
+        };
+        assert_eq!(token("dev"), "base-token");
+        assert_eq!(token("tfe"), "");
+        assert_eq!(token("vault"), "");
+        assert_eq!(token("own"), "own-token");
+        let config = load_with(&profile("vault")).unwrap();
+        assert_eq!(
+            config.get_string("token_command").unwrap(),
+            "vault read -field=token secret/tfe"
+        );
+    }
+
+    #[test]
+    fn profile_precedence() {
+        let mut env = ScopedEnv::new("profile-precedence");
+        env.write(
+            "xdg/tfct/config.toml",
+            "log = \"warn\"\n[profiles.dev]\nlog = \"debug\"\norg = \"user-org\"",
+        );
+        env.write("settings.toml", "org = \"local-org\"");
+        let config = load_with(&profile("dev")).unwrap();
+        assert_eq!(config.get_string("log").unwrap(), "debug");
+        assert_eq!(config.get_string("org").unwrap(), "user-org");
+        env.set("ORG", "env-org");
+        let config = load_with(&profile("dev")).unwrap();
+        assert_eq!(config.get_string("org").unwrap(), "env-org");
+    }
+
+    #[test]
+    fn profile_not_found() {
+        let env = ScopedEnv::new("profile-not-found");
+        env.write("settings.toml", PROFILES);
+        let err = load_with(&profile("staging")).unwrap_err().to_string();
+        assert!(err.starts_with("Profile staging not found"), "{}", err);
+        assert!(err.contains("dev"), "{}", err);
+        env.write("settings.toml", "org = \"base-org\"");
+        let err = load_with(&profile("dev")).unwrap_err().to_string();
+        assert_eq!(err, "Profile dev not found, available profiles: []");
+    }
+
+    #[test]
+    fn config_file_precedence() {
+        let mut env = ScopedEnv::new("config-precedence");
+        env.write(
+            "xdg/tfct/config.toml",
+            "log = \"user\"\norg = \"user\"\nproject = \"user\"\noutput = \"user\"",
+        );
+        env.write(
+            "settings.toml",
+            "log = \"local\"\norg = \"local\"\nproject = \"local\"",
+        );
+        let env_file = env.write("env.toml", "log = \"env\"\norg = \"env\"");
+        let option_file = env.write("option.toml", "log = \"option\"");
+        env.set(CONFIG_ENV, &env_file);
+        let options =
+            LoadOptions { config: Some(option_file), ..Default::default() };
+        let config = load_with(&options).unwrap();
+        assert_eq!(config.get_string("log").unwrap(), "option");
+        assert_eq!(config.get_string("org").unwrap(), "env");
+        assert_eq!(config.get_string("project").unwrap(), "local");
+        assert_eq!(config.get_string("output").unwrap(), "user");
+    }
+
+    #[test]
+    fn required_config_files() {
+        let mut env = ScopedEnv::new("config-required");
+        // The default locations are optional
+        assert!(load_with(&LoadOptions::default()).is_ok());
+        let missing = env.dir.join("missing.toml");
+        let options =
+            LoadOptions { config: Some(missing.clone()), ..Default::default() };
+        assert!(load_with(&options).is_err());
+        env.set(CONFIG_ENV, &missing);
+        assert!(load_with(&LoadOptions::default()).is_err());
+        // An empty variable is the same as not setting it
+        env.set(CONFIG_ENV, "");
+        assert!(load_with(&LoadOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn user_config_file_location() {
+        let mut env = ScopedEnv::new("config-user");
+        let home = env.dir.clone();
+        assert_eq!(
+            user_config_file().unwrap(),
+            home.join("xdg").join("tfct").join("config.toml")
+        );
+        // Falls back to the home directory when unset or empty
+        let fallback = home.join(".config").join("tfct").join("config.toml");
+        env.set("HOME", &home);
+        env.set("XDG_CONFIG_HOME", "");
+        assert_eq!(user_config_file().unwrap(), fallback);
+        env.remove("XDG_CONFIG_HOME");
+        assert_eq!(user_config_file().unwrap(), fallback);
     }
 }
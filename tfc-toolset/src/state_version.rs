@@ -0,0 +1,191 @@
+use crate::{
+    build_request,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    settings::Core,
+    workspace, Paginator,
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use surf::{http::Method, Client, RequestBuilder};
+use url::Url;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Attributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terraform_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources_processed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosted_state_download_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosted_json_state_download_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StateVersion {
+    pub id: String,
+    pub attributes: Attributes,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct StateVersionOuter {
+    pub data: StateVersion,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OutputAttributes {
+    pub name: String,
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub output_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detailed_type: Option<Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Output {
+    pub id: String,
+    pub attributes: OutputAttributes,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct OutputOuter {
+    pub data: Output,
+}
+
+/// Retrieve the current state version of a workspace
+pub async fn current(
+    workspace_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<StateVersion, ToolError> {
+    info!("Retrieving current state version for workspace {}.", workspace_id);
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/current-state-version",
+        config.base_url, workspace_id
+    ))?;
+    let req = build_request(Method::Get, url, config, None);
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        info!("Successfully retrieved current state version!");
+        let state_version: StateVersionOuter =
+            res.body_json().await.map_err(surf_to_tool_error)?;
+        Ok(state_version.data)
+    } else {
+        error!("Failed to retrieve current state version :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+/// List the state versions of a workspace, newest first
+pub async fn list(
+    workspace_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Vec<StateVersion>, ToolError> {
+    // State versions can only be filtered by workspace name
+    let workspace =
+        workspace::show(workspace_id, config, client.clone()).await?;
+    let workspace_name = workspace.attributes.name.unwrap_or_default();
+    info!("Retrieving state versions for workspace {}.", workspace_name);
+    let url = Url::parse_with_params(
+        &format!("{}/state-versions", config.base_url),
+        &[
+            ("filter[workspace][name]", workspace_name.as_str()),
+            ("filter[organization][name]", config.org.as_str()),
+        ],
+    )?;
+    let state_versions = Paginator::new(url, config, client)?.collect().await?;
+    info!("Successfully retrieved state versions!");
+    Ok(state_versions)
+}
+
+/// List the outputs of a state version, the values of sensitive outputs are
+/// always null here and have to be retrieved with `output`
+pub async fn outputs(
+    state_version_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Vec<Output>, ToolError> {
+    info!("Retrieving outputs for state version {}.", state_version_id);
+    let url = Url::parse(&format!(
+        "{}/state-versions/{}/outputs",
+        config.base_url, state_version_id
+    ))?;
+    // A partial list of outputs is never useful, so always retrieve them all
+    let outputs = Paginator::new(url, config, client)?
+        .start_page(1)
+        .max_depth(0)
+        .collect()
+        .await?;
+    info!("Successfully retrieved state version outputs!");
+    Ok(outputs)
+}
+
+/// Retrieve a single state version output
+///
+/// Unlike the list of outputs this includes the value of a sensitive output,
+/// provided the token is allowed to read it.
+pub async fn output(
+    output_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Output, ToolError> {
+    info!("Retrieving state version output {}.", output_id);
+    let url = Url::parse(&format!(
+        "{}/state-version-outputs/{}",
+        config.base_url, output_id
+    ))?;
+    let req = build_request(Method::Get, url, config, None);
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        info!("Successfully retrieved state version output!");
+        let output: OutputOuter =
+            res.body_json().await.map_err(surf_to_tool_error)?;
+        Ok(output.data)
+    } else {
+        error!("Failed to retrieve state version output :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
+
+/// Download the raw state file of a state version
+pub async fn download(
+    state_version: &StateVersion,
+    client: Client,
+) -> Result<Vec<u8>, ToolError> {
+    let download_url = state_version
+        .attributes
+        .hosted_state_download_url
+        .as_ref()
+        .ok_or_else(|| {
+            ToolError::General(anyhow::anyhow!(
+                "State version {} does not have a download url",
+                state_version.id
+            ))
+        })?;
+    info!("Downloading state version {}.", state_version.id);
+    // The download url is pre-signed so the token is not sent
+    let req =
+        RequestBuilder::new(Method::Get, Url::parse(download_url)?).build();
+    let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
+    if res.status().is_success() {
+        info!("Successfully downloaded state version!");
+        res.body_bytes().await.map_err(surf_to_tool_error)
+    } else {
+        error!("Failed to download state version :(");
+        Err(response_to_tool_error(&mut res).await)
+    }
+}
@@ -1,11 +1,8 @@
-use std::vec;
-
 use crate::{
     build_request,
-    error::{surf_to_tool_error, ToolError},
-    set_page_number,
+    error::{response_to_tool_error, ToolError},
     settings::Core,
-    workspace, Meta, BASE_URL,
+    workspace, Meta, Paginator,
 };
 
 use log::{error, info};
@@ -62,101 +59,14 @@ pub async fn list(
     config: &Core,
     client: Client,
 ) -> Result<Tags, ToolError> {
-    info!(
-        "Retrieving the initial list of tags for workspace {}.",
-        workspace_id
-    );
-    let params = vec![
-        ("page[size]", config.pagination.page_size.clone()),
-        ("page[number]", config.pagination.start_page.clone()),
-    ];
-    let url = Url::parse_with_params(
-        &format!("{}/workspaces/{}/relationships/tags", BASE_URL, workspace_id),
-        &params,
-    )?;
-    let req = build_request(Method::Get, url.clone(), config, None);
-    let mut tag_list: Tags = match client.send(req).await {
-        Ok(mut res) => {
-            if res.status().is_success() {
-                info!("Tags for workspace {} retrieved.", workspace_id);
-                match res.body_json().await {
-                    Ok(t) => t,
-                    Err(e) => {
-                        error!("{:#?}", e);
-                        return Err(ToolError::General(anyhow::anyhow!(e)));
-                    }
-                }
-            } else {
-                error!(
-                    "Failed to retrieve tags for workspace {}.",
-                    workspace_id
-                );
-                let error =
-                    res.body_string().await.map_err(|e| e.into_inner())?;
-                return Err(ToolError::General(anyhow::anyhow!(error)));
-            }
-        }
-        Err(e) => {
-            return Err(ToolError::General(e.into_inner()));
-        }
-    };
-    // Need to check pagination
-    if let Some(meta) = tag_list.meta.clone() {
-        let max_depth = config.pagination.max_depth.parse::<u32>()?;
-        if max_depth > 1 || max_depth == 0 {
-            let current_depth: u32 = 1;
-            if let Some(next_page) = meta.pagination.next_page {
-                if max_depth == 0 || current_depth < max_depth {
-                    let num_pages: u32 = if max_depth
-                        >= meta.pagination.total_pages
-                        || max_depth == 0
-                    {
-                        meta.pagination.total_pages
-                    } else {
-                        max_depth
-                    };
-
-                    // Get the next page and merge the result
-                    for n in next_page..=num_pages {
-                        let u = url.clone();
-                        info!("Retrieving tags page {}.", &n);
-                        let u = match set_page_number(n, u) {
-                            Some(u) => u,
-                            None => {
-                                error!("Failed to set page number.");
-                                return Err(ToolError::Pagination(
-                                    "Failed to set page number.".to_string(),
-                                ));
-                            }
-                        };
-                        let req =
-                            build_request(Method::Get, u.clone(), config, None);
-                        let mut response = client
-                            .send(req)
-                            .await
-                            .map_err(surf_to_tool_error)?;
-                        if response.status().is_success() {
-                            let tag_pages: Result<Tags, ToolError> = response
-                                .body_json()
-                                .await
-                                .map_err(surf_to_tool_error);
-                            match tag_pages {
-                                Ok(mut t) => {
-                                    tag_list.data.append(&mut t.data);
-                                }
-                                Err(e) => {
-                                    error!("{:#?}", e);
-                                    return Err(e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+    info!("Retrieving the list of tags for workspace {}.", workspace_id);
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/relationships/tags",
+        config.base_url, workspace_id
+    ))?;
+    let tags = Paginator::new(url, config, client)?.collect().await?;
     info!("Finished retrieving tags.");
-    Ok(tag_list)
+    Ok(Tags::new(tags))
 }
 
 pub async fn list_by_name(
@@ -178,7 +88,7 @@ pub async fn add(
     info!("Tagging workspace {}.", workspace_id);
     let url = Url::parse(&format!(
         "{}/workspaces/{}/relationships/tags",
-        BASE_URL, workspace_id
+        config.base_url, workspace_id
     ))?;
     let tags = tags.into_iter().map(Tag::from).collect::<Vec<Tag>>();
     let req =
@@ -190,9 +100,7 @@ pub async fn add(
                 Ok(())
             } else {
                 error!("Failed to tag workspace {}.", workspace_id);
-                let error =
-                    r.body_string().await.map_err(|e| e.into_inner())?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -219,7 +127,7 @@ pub async fn remove(
     info!("Removing tags from workspace {}.", workspace_id);
     let url = Url::parse(&format!(
         "{}/workspaces/{}/relationships/tags",
-        BASE_URL, workspace_id
+        config.base_url, workspace_id
     ))?;
     let tags = tags.into_iter().map(Tag::from).collect::<Vec<Tag>>();
     let req = build_request(
@@ -238,9 +146,7 @@ pub async fn remove(
                     "Failed to remove tags from workspace {}.",
                     workspace_id
                 );
-                let error =
-                    r.body_string().await.map_err(|e| e.into_inner())?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -1,11 +1,12 @@
 use crate::{
     build_request,
-    error::ToolError,
+    error::{response_to_tool_error, ToolError},
     settings::Core,
     workspace::{Workspace, WorkspaceVariables},
-    BASE_URL,
+    Paginator,
 };
 
+use futures::{stream, StreamExt};
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -48,7 +49,7 @@ impl FromStr for Variable {
             let category = if var_split[2].is_empty() {
                 Category::default()
             } else {
-                Category::from(var_split[2].to_string())
+                var_split[2].parse::<Category>()?
             };
             let hcl = if var_split[3].is_empty() {
                 None
@@ -109,6 +110,18 @@ impl Display for Category {
     }
 }
 
+impl FromStr for Category {
+    type Err = ToolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "terraform" => Ok(Category::Terraform),
+            "env" => Ok(Category::Env),
+            _ => Err(ToolError::InvalidVariableCategory(s.to_string())),
+        }
+    }
+}
+
 impl From<String> for Category {
     fn from(s: String) -> Self {
         match s.as_str() {
@@ -153,8 +166,10 @@ pub async fn create(
         "Creating variable: {} in workspace: {}",
         var.attributes.key, workspace_id
     );
-    let url =
-        Url::parse(&format!("{}/workspaces/{}/vars/", BASE_URL, workspace_id))?;
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/vars/",
+        config.base_url, workspace_id
+    ))?;
     let req = build_request(
         Method::Post,
         url,
@@ -169,52 +184,346 @@ pub async fn create(
                 Ok(body.data)
             } else {
                 error!("Failed to create variable :(");
-                let error =
-                    res.body_string().await.map_err(|e| e.into_inner())?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut res).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
     }
 }
-pub async fn list(
+
+pub async fn update(
     workspace_id: &str,
+    variable_id: &str,
+    attributes: Attributes,
     config: &Core,
     client: Client,
-) -> Result<Vec<Variable>, ToolError> {
-    let url =
-        Url::parse(&format!("{}/workspaces/{}/vars/", BASE_URL, workspace_id))?;
-    let req = build_request(Method::Get, url, config, None);
+) -> Result<Variable, ToolError> {
+    info!(
+        "Updating variable: {} in workspace: {}",
+        attributes.key, workspace_id
+    );
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/vars/{}",
+        config.base_url, workspace_id, variable_id
+    ))?;
+    let var = Variable {
+        relationship_type: "vars".to_string(),
+        id: Some(variable_id.to_string()),
+        attributes,
+    };
+    let mut body = json!(VariableOuter { data: var });
+    // Leaving the value out keeps the current one, rather than clearing it
+    if let Some(attributes) = body["data"]["attributes"].as_object_mut() {
+        if attributes.get("value").is_some_and(|v| v.is_null()) {
+            attributes.remove("value");
+        }
+    }
+    let req = build_request(Method::Patch, url, config, Some(body));
     match client.send(req).await {
         Ok(mut res) => {
             if res.status().is_success() {
-                info!("Successfully retrieved variables!");
-                let body: VariablesOuter =
+                info!("Successfully updated variable!");
+                let body: VariableOuter =
                     res.body_json().await.map_err(|e| e.into_inner())?;
                 Ok(body.data)
             } else {
-                error!("Failed to list variables :(");
-                let error =
-                    res.body_string().await.map_err(|e| e.into_inner())?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                error!("Failed to update variable :(");
+                Err(response_to_tool_error(&mut res).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
     }
 }
 
+/// Find a variable by key and category, keys are only unique per category
+pub fn find<'a>(
+    variables: &'a [Variable],
+    key: &str,
+    category: &Category,
+) -> Option<&'a Variable> {
+    variables
+        .iter()
+        .find(|v| v.attributes.key == key && &v.attributes.category == category)
+}
+
+/// Create the variable, or update it if a variable with the same key and
+/// category already exists in the workspace
+pub async fn upsert(
+    workspace_id: &str,
+    var: Variable,
+    config: &Core,
+    client: Client,
+) -> Result<Variable, ToolError> {
+    let mut variables =
+        upsert_all(workspace_id, vec![var], config, client).await?;
+    Ok(variables.remove(0))
+}
+
+/// Upsert many variables, retrieving the existing variables only once
+pub async fn upsert_all(
+    workspace_id: &str,
+    vars: Vec<Variable>,
+    config: &Core,
+    client: Client,
+) -> Result<Vec<Variable>, ToolError> {
+    let existing = list(workspace_id, config, client.clone()).await?;
+    let mut variables = Vec::with_capacity(vars.len());
+    for var in vars {
+        let found =
+            find(&existing, &var.attributes.key, &var.attributes.category)
+                .and_then(|v| v.id.clone());
+        let variable = match found {
+            Some(variable_id) => {
+                update(
+                    workspace_id,
+                    &variable_id,
+                    var.attributes,
+                    config,
+                    client.clone(),
+                )
+                .await?
+            }
+            None => create(workspace_id, var, config, client.clone()).await?,
+        };
+        variables.push(variable);
+    }
+    Ok(variables)
+}
+
+/// The changes needed to make a workspace's variables match a desired state
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SyncPlan {
+    pub creates: Vec<Variable>,
+    pub updates: Vec<SyncUpdate>,
+    pub deletes: Vec<Variable>,
+    /// Sensitive variables that don't exist yet and have no desired value
+    pub skipped: Vec<Variable>,
+    /// Sensitive variables the desired state makes non-sensitive, which the
+    /// API doesn't allow, so they have to be deleted and created again
+    pub conflicts: Vec<SyncUpdate>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SyncUpdate {
+    pub current: Variable,
+    pub desired: Variable,
+}
+
+impl SyncPlan {
+    pub fn has_changes(&self) -> bool {
+        !self.creates.is_empty()
+            || !self.updates.is_empty()
+            || !self.deletes.is_empty()
+    }
+}
+
+// Whether the current variable differs from the desired one, fields that
+// are omitted from the desired variable are left as they are
+fn needs_update(current: &Attributes, desired: &Attributes) -> bool {
+    let sensitive = current.sensitive.unwrap_or(false);
+    // The value of a sensitive variable is never returned, so when a desired
+    // value is given it can't be compared and is always written
+    let value_changed = match &desired.value {
+        Some(value) => sensitive || current.value.as_ref() != Some(value),
+        None => false,
+    };
+    value_changed
+        || desired.description.is_some()
+            && desired.description != current.description
+        || desired.hcl.is_some() && desired.hcl != current.hcl
+        || desired.sensitive.is_some() && desired.sensitive != current.sensitive
+}
+
+/// Compare the current variables with the desired ones, only variables in
+/// the given categories are considered, an empty list means every category
+pub fn diff(
+    current: &[Variable],
+    desired: &[Variable],
+    categories: &[Category],
+) -> SyncPlan {
+    let in_scope = |var: &&Variable| {
+        categories.is_empty() || categories.contains(&var.attributes.category)
+    };
+    let mut plan = SyncPlan::default();
+    for desired_var in desired.iter().filter(in_scope) {
+        let attributes = &desired_var.attributes;
+        match find(current, &attributes.key, &attributes.category) {
+            Some(current_var) => {
+                if current_var.attributes.sensitive.unwrap_or(false)
+                    && attributes.sensitive == Some(false)
+                {
+                    plan.conflicts.push(SyncUpdate {
+                        current: current_var.clone(),
+                        desired: desired_var.clone(),
+                    });
+                } else if needs_update(&current_var.attributes, attributes) {
+                    plan.updates.push(SyncUpdate {
+                        current: current_var.clone(),
+                        desired: desired_var.clone(),
+                    });
+                }
+            }
+            None => {
+                if attributes.sensitive.unwrap_or(false)
+                    && attributes.value.is_none()
+                {
+                    plan.skipped.push(desired_var.clone());
+                } else {
+                    plan.creates.push(desired_var.clone());
+                }
+            }
+        }
+    }
+    for current_var in current.iter().filter(in_scope) {
+        let attributes = &current_var.attributes;
+        if find(desired, &attributes.key, &attributes.category).is_none() {
+            plan.deletes.push(current_var.clone());
+        }
+    }
+    plan
+}
+
+/// Build a sync plan for a workspace against the desired variables
+pub async fn plan_sync(
+    workspace_id: &str,
+    desired: &[Variable],
+    categories: &[Category],
+    config: &Core,
+    client: Client,
+) -> Result<SyncPlan, ToolError> {
+    let current = list(workspace_id, config, client).await?;
+    Ok(diff(&current, desired, categories))
+}
+
+/// Apply a sync plan to a workspace
+pub async fn sync(
+    workspace_id: &str,
+    plan: &SyncPlan,
+    config: &Core,
+    client: Client,
+) -> Result<(), ToolError> {
+    info!("Syncing variables for workspace {}.", workspace_id);
+    for var in &plan.creates {
+        create(workspace_id, var.clone(), config, client.clone()).await?;
+    }
+    for update_var in &plan.updates {
+        if let Some(variable_id) = &update_var.current.id {
+            update(
+                workspace_id,
+                variable_id,
+                update_var.desired.attributes.clone(),
+                config,
+                client.clone(),
+            )
+            .await?;
+        }
+    }
+    for var in &plan.deletes {
+        if let Some(variable_id) = &var.id {
+            delete(variable_id, workspace_id, config, client.clone()).await?;
+        }
+    }
+    info!("Successfully synced variables!");
+    Ok(())
+}
+
+pub async fn list(
+    workspace_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Vec<Variable>, ToolError> {
+    info!("Retrieving variables for workspace {}.", workspace_id);
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/vars/",
+        config.base_url, workspace_id
+    ))?;
+    let variables = Paginator::new(url, config, client)?.collect().await?;
+    info!("Successfully retrieved variables!");
+    Ok(variables)
+}
+
+/// A workspace whose variables could not be retrieved
+#[derive(Debug)]
+pub struct BatchFailure {
+    pub workspace: Workspace,
+    pub error: ToolError,
+}
+
+/// The variables retrieved for a batch of workspaces, in the same order as
+/// the workspaces were given
+#[derive(Debug, Default)]
+pub struct VariablesBatch {
+    pub workspaces: Vec<WorkspaceVariables>,
+    pub failures: Vec<BatchFailure>,
+}
+
+impl VariablesBatch {
+    /// Log each failure, `into_result` only returns the first
+    pub fn log_failures(&self) {
+        for failure in &self.failures {
+            error!(
+                "Failed to retrieve variables for workspace {}: {}",
+                failure.workspace.id, failure.error
+            );
+        }
+    }
+
+    /// The variables for every workspace, or the first failure if any
+    /// workspace failed
+    pub fn into_result(self) -> Result<Vec<WorkspaceVariables>, ToolError> {
+        match self.failures.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(self.workspaces),
+        }
+    }
+}
+
+/// Retrieve the variables for each workspace, up to `batch_concurrency`
+/// workspaces at a time
+///
+/// A workspace that fails is recorded in `VariablesBatch::failures` rather
+/// than stopping the rest of the batch. Requests still pass through the
+/// client middleware, so any rate limiting there applies across the batch.
 pub async fn list_batch(
     config: &Core,
     client: Client,
     workspaces: Vec<Workspace>,
-) -> Result<Vec<WorkspaceVariables>, ToolError> {
-    let mut workspaces_variables: Vec<WorkspaceVariables> = vec![];
-    // Get the variables for each workspace
-    for workspace in workspaces {
-        let variables = list(&workspace.id, config, client.clone()).await?;
-        workspaces_variables.push(WorkspaceVariables { workspace, variables });
+) -> VariablesBatch {
+    info!(
+        "Retrieving variables for {} workspaces, {} at a time.",
+        workspaces.len(),
+        config.batch_concurrency
+    );
+    let results: Vec<(Workspace, Result<Vec<Variable>, ToolError>)> =
+        stream::iter(workspaces)
+            .map(|workspace| {
+                let client = client.clone();
+                async move {
+                    // No need for a request when the variables were
+                    // included with the workspace
+                    let variables = match workspace.included_vars() {
+                        Some(variables) => Ok(variables),
+                        None => list(&workspace.id, config, client).await,
+                    };
+                    (workspace, variables)
+                }
+            })
+            // Unlike buffer_unordered this keeps the results in order
+            .buffered(config.batch_concurrency.max(1))
+            .collect()
+            .await;
+    let mut batch = VariablesBatch::default();
+    for (workspace, variables) in results {
+        match variables {
+            Ok(variables) => batch
+                .workspaces
+                .push(WorkspaceVariables { workspace, variables }),
+            Err(error) => {
+                batch.failures.push(BatchFailure { workspace, error })
+            }
+        }
     }
-    Ok(workspaces_variables)
+    batch
 }
 
 pub async fn delete(
@@ -229,7 +538,7 @@ pub async fn delete(
     );
     let url = Url::parse(&format!(
         "{}/workspaces/{}/vars/{}",
-        BASE_URL, workspace_id, variable_id
+        config.base_url, workspace_id, variable_id
     ))?;
     let req = build_request(Method::Delete, url, config, None);
     match client.send(req).await {
@@ -239,11 +548,222 @@ pub async fn delete(
                 Ok(())
             } else {
                 error!("Failed to delete variable :(");
-                let error =
-                    res.body_string().await.map_err(|e| e.into_inner())?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut res).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(key: &str, value: Option<&str>) -> Attributes {
+        Attributes {
+            key: key.to_string(),
+            value: value.map(str::to_string),
+            description: None,
+            category: Category::Terraform,
+            hcl: None,
+            sensitive: None,
+        }
+    }
+
+    fn var(id: Option<&str>, attributes: Attributes) -> Variable {
+        Variable {
+            relationship_type: "vars".to_string(),
+            id: id.map(str::to_string),
+            attributes,
+        }
+    }
+
+    fn sensitive(mut attributes: Attributes) -> Attributes {
+        attributes.sensitive = Some(true);
+        attributes
+    }
+
+    fn env(mut attributes: Attributes) -> Attributes {
+        attributes.category = Category::Env;
+        attributes
+    }
+
+    #[test]
+    fn category_from_str() {
+        assert_eq!(
+            "terraform".parse::<Category>().unwrap(),
+            Category::Terraform
+        );
+        assert_eq!("env".parse::<Category>().unwrap(), Category::Env);
+        for invalid in ["envv", "Env", "", "terraform "] {
+            assert!(matches!(
+                invalid.parse::<Category>(),
+                Err(ToolError::InvalidVariableCategory(s)) if s == invalid
+            ));
+        }
+    }
+
+    #[test]
+    fn variable_from_str_rejects_unknown_category() {
+        let var = "region=us-east-1::env::".parse::<Variable>().unwrap();
+        assert_eq!(var.attributes.category, Category::Env);
+        assert!(matches!(
+            "region=us-east-1::envv::".parse::<Variable>(),
+            Err(ToolError::InvalidVariableCategory(_))
+        ));
+    }
+
+    #[test]
+    fn needs_update_compares_given_fields() {
+        let current = attributes("region", Some("us-east-1"));
+        assert!(!needs_update(&current, &current));
+        // Fields omitted from the desired variable are left as they are
+        assert!(!needs_update(&current, &attributes("region", None)));
+        assert!(needs_update(
+            &current,
+            &attributes("region", Some("eu-west-1"))
+        ));
+        let mut desired = attributes("region", None);
+        desired.description = Some("The region".to_string());
+        assert!(needs_update(&current, &desired));
+        let mut desired = attributes("region", None);
+        desired.hcl = Some(true);
+        assert!(needs_update(&current, &desired));
+        let mut desired = attributes("region", None);
+        desired.hcl = Some(false);
+        assert!(needs_update(&current, &desired));
+    }
+
+    #[test]
+    fn needs_update_sensitive() {
+        // The API never returns the value of a sensitive variable
+        let current = sensitive(attributes("password", None));
+        assert!(needs_update(
+            &current,
+            &sensitive(attributes("password", Some("secret")))
+        ));
+        assert!(!needs_update(
+            &current,
+            &sensitive(attributes("password", None))
+        ));
+        assert!(!needs_update(&current, &attributes("password", None)));
+        let mut desired = attributes("password", None);
+        desired.sensitive = Some(false);
+        assert!(needs_update(&current, &desired));
+        let current = attributes("password", Some("secret"));
+        assert!(needs_update(
+            &current,
+            &sensitive(attributes("password", None))
+        ));
+    }
+
+    #[test]
+    fn diff_creates_updates_and_deletes() {
+        let current = vec![
+            var(Some("var-1"), attributes("region", Some("us-east-1"))),
+            var(Some("var-2"), attributes("unchanged", Some("same"))),
+            var(Some("var-3"), attributes("removed", Some("old"))),
+        ];
+        let desired = vec![
+            var(None, attributes("region", Some("eu-west-1"))),
+            var(None, attributes("unchanged", Some("same"))),
+            var(None, attributes("added", Some("new"))),
+        ];
+        let plan = diff(&current, &desired, &[]);
+        assert!(plan.has_changes());
+        assert_eq!(plan.creates, vec![desired[2].clone()]);
+        assert_eq!(plan.updates.len(), 1);
+        assert_eq!(plan.updates[0].current, current[0]);
+        assert_eq!(plan.updates[0].desired, desired[0]);
+        assert_eq!(plan.deletes, vec![current[2].clone()]);
+        assert!(plan.skipped.is_empty());
+    }
+
+    #[test]
+    fn diff_without_changes() {
+        let current =
+            vec![var(Some("var-1"), attributes("region", Some("us-east-1")))];
+        let desired = vec![var(None, attributes("region", Some("us-east-1")))];
+        let plan = diff(&current, &desired, &[]);
+        assert!(!plan.has_changes());
+        assert!(!diff(&[], &[], &[]).has_changes());
+    }
+
+    #[test]
+    fn diff_matches_on_key_and_category() {
+        let current = vec![var(
+            Some("var-1"),
+            env(attributes("REGION", Some("us-east-1"))),
+        )];
+        let desired = vec![var(None, attributes("REGION", Some("us-east-1")))];
+        let plan = diff(&current, &desired, &[]);
+        assert_eq!(plan.creates, desired);
+        assert_eq!(plan.deletes, current);
+    }
+
+    #[test]
+    fn diff_only_considers_given_categories() {
+        let current = vec![
+            var(Some("var-1"), attributes("region", Some("us-east-1"))),
+            var(Some("var-2"), env(attributes("TF_LOG", Some("debug")))),
+        ];
+        let desired =
+            vec![var(None, env(attributes("AWS_REGION", Some("eu-west-1"))))];
+        let plan = diff(&current, &desired, &[Category::Env]);
+        assert_eq!(plan.creates, desired);
+        assert_eq!(plan.deletes, vec![current[1].clone()]);
+        let plan = diff(&current, &desired, &[Category::Terraform]);
+        assert!(plan.creates.is_empty());
+        assert_eq!(plan.deletes, vec![current[0].clone()]);
+    }
+
+    #[test]
+    fn diff_sensitive_variables() {
+        let current = vec![
+            var(Some("var-1"), sensitive(attributes("password", None))),
+            var(Some("var-2"), sensitive(attributes("api_key", None))),
+        ];
+        let desired = vec![
+            // A sensitive value can't be compared so it is always written
+            var(None, sensitive(attributes("password", Some("secret")))),
+            // Without a value the existing variable is kept as is
+            var(None, sensitive(attributes("api_key", None))),
+            // A new sensitive variable needs a value to be created
+            var(None, sensitive(attributes("token", None))),
+            var(None, sensitive(attributes("db_password", Some("hunter2")))),
+        ];
+        let plan = diff(&current, &desired, &[]);
+        assert_eq!(plan.updates.len(), 1);
+        assert_eq!(plan.updates[0].current, current[0]);
+        assert_eq!(plan.skipped, vec![desired[2].clone()]);
+        assert_eq!(plan.creates, vec![desired[3].clone()]);
+        assert!(plan.deletes.is_empty());
+    }
+
+    #[test]
+    fn diff_sensitive_to_non_sensitive() {
+        let current =
+            vec![var(Some("var-1"), sensitive(attributes("password", None)))];
+        let mut desired = attributes("password", Some("secret"));
+        desired.sensitive = Some(false);
+        let desired = vec![var(None, desired)];
+        let plan = diff(&current, &desired, &[]);
+        assert!(!plan.has_changes());
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].current, current[0]);
+        assert_eq!(plan.conflicts[0].desired, desired[0]);
+        // Leaving sensitive out keeps the variable as it is
+        let desired = vec![var(None, attributes("password", Some("secret")))];
+        let plan = diff(&current, &desired, &[]);
+        assert_eq!(plan.updates.len(), 1);
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn skipped_variables_are_not_changes() {
+        let desired = vec![var(None, sensitive(attributes("token", None)))];
+        let plan = diff(&[], &desired, &[]);
+        assert_eq!(plan.skipped, desired);
+        assert!(!plan.has_changes());
+    }
+}
@@ -1,11 +1,10 @@
 use crate::{
     build_request,
-    error::{surf_to_tool_error, ToolError},
-    set_page_number,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
     settings::Core,
     variable::{Variable, VariablesOuter},
     workspace::Workspace,
-    Meta, BASE_URL,
+    Meta, Paginator,
 };
 
 use log::{error, info};
@@ -168,72 +167,15 @@ impl From<Vec<Workspace>> for ApplyVarSetOuter {
     }
 }
 
-async fn check_pagination(
-    meta: Meta,
-    var_set_list: &mut VarSets,
-    url: Url,
-    config: &Core,
-    client: Client,
-) -> Result<(), ToolError> {
-    let max_depth = config.pagination.max_depth.parse::<u32>()?;
-    if max_depth > 1 || max_depth == 0 {
-        let current_depth: u32 = 1;
-        if let Some(next_page) = meta.pagination.next_page {
-            if max_depth == 0 || current_depth < max_depth {
-                let num_pages: u32 = if max_depth >= meta.pagination.total_pages
-                    || max_depth == 0
-                {
-                    meta.pagination.total_pages
-                } else {
-                    max_depth
-                };
-
-                // Get the next page and merge the result
-                for n in next_page..=num_pages {
-                    let u = url.clone();
-                    info!("Retrieving variable set page {}.", &n);
-                    let u = match set_page_number(n, u) {
-                        Some(u) => u,
-                        None => {
-                            error!("Failed to set page number.");
-                            return Err(ToolError::Pagination(
-                                "Failed to set page number.".to_string(),
-                            ));
-                        }
-                    };
-                    let req =
-                        build_request(Method::Get, u.clone(), config, None);
-                    let mut response =
-                        client.send(req).await.map_err(surf_to_tool_error)?;
-                    if response.status().is_success() {
-                        let var_set_pages: Result<VarSets, ToolError> =
-                            response
-                                .body_json()
-                                .await
-                                .map_err(surf_to_tool_error);
-                        match var_set_pages {
-                            Ok(mut t) => {
-                                var_set_list.data.append(&mut t.data);
-                            }
-                            Err(e) => {
-                                error!("{:#?}", e);
-                                return Err(e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    Ok(())
-}
-
 pub async fn show(
     variable_set_id: &str,
     config: &Core,
     client: Client,
 ) -> Result<VarSet, ToolError> {
-    let url = Url::parse(&format!("{}/varsets/{}", BASE_URL, variable_set_id))?;
+    let url = Url::parse(&format!(
+        "{}/varsets/{}",
+        config.base_url, variable_set_id
+    ))?;
     let req = build_request(Method::Get, url, config, None);
     let mut res = client.send(req).await.map_err(surf_to_tool_error)?;
     if res.status().is_success() {
@@ -242,8 +184,7 @@ pub async fn show(
         Ok(var_set.data)
     } else {
         error!("Failed to show variable set");
-        let error = res.body_string().await.map_err(surf_to_tool_error)?;
-        Err(ToolError::General(anyhow::anyhow!(error)))
+        Err(response_to_tool_error(&mut res).await)
     }
 }
 
@@ -251,47 +192,14 @@ pub async fn list_by_org(
     config: &Core,
     client: Client,
 ) -> Result<VarSets, ToolError> {
-    info!(
-        "Retrieving the initial list of variable sets for org {}.",
-        config.org
-    );
-    let params = vec![
-        ("page[size]", config.pagination.page_size.clone()),
-        ("page[number]", config.pagination.start_page.clone()),
-    ];
-    let url = Url::parse_with_params(
-        &format!("{}/organizations/{}/varsets", BASE_URL, config.org),
-        &params,
-    )?;
-    let req = build_request(Method::Get, url.clone(), config, None);
-    let mut var_set_list: VarSets = match client.send(req).await {
-        Ok(mut res) => {
-            if res.status().is_success() {
-                info!("Variable sets for org {} retrieved.", config.org);
-                match res.body_json().await {
-                    Ok(t) => t,
-                    Err(e) => {
-                        error!("{:#?}", e);
-                        return Err(ToolError::General(anyhow::anyhow!(e)));
-                    }
-                }
-            } else {
-                error!("Failed to fetch variable sets for org {}.", config.org);
-                let error =
-                    res.body_string().await.map_err(surf_to_tool_error)?;
-                return Err(ToolError::General(anyhow::anyhow!(error)));
-            }
-        }
-        Err(e) => {
-            return Err(ToolError::General(anyhow::anyhow!(e)));
-        }
-    };
-    // Need to check pagination
-    if let Some(meta) = var_set_list.meta.clone() {
-        check_pagination(meta, &mut var_set_list, url, config, client).await?;
-    }
+    info!("Retrieving the list of variable sets for org {}.", config.org);
+    let url = Url::parse(&format!(
+        "{}/organizations/{}/varsets",
+        config.base_url, config.org
+    ))?;
+    let var_sets = Paginator::new(url, config, client)?.collect().await?;
     info!("Finished retrieving variable sets.");
-    Ok(var_set_list)
+    Ok(VarSets::new(var_sets))
 }
 
 pub async fn list_by_project(
@@ -304,50 +212,14 @@ pub async fn list_by_project(
         )));
     }
     let project_id = config.project.clone().unwrap();
-    info!(
-        "Retrieving the initial list of variable sets for project {}.",
-        project_id
-    );
-    let params = vec![
-        ("page[size]", config.pagination.page_size.clone()),
-        ("page[number]", config.pagination.start_page.clone()),
-    ];
-    let url = Url::parse_with_params(
-        &format!("{}/projects/{}/varsets", BASE_URL, project_id),
-        &params,
-    )?;
-    let req = build_request(Method::Get, url.clone(), config, None);
-    let mut var_set_list: VarSets = match client.send(req).await {
-        Ok(mut res) => {
-            if res.status().is_success() {
-                info!("Variable sets for project {} retrieved.", project_id);
-                match res.body_json().await {
-                    Ok(t) => t,
-                    Err(e) => {
-                        error!("{:#?}", e);
-                        return Err(ToolError::General(anyhow::anyhow!(e)));
-                    }
-                }
-            } else {
-                error!(
-                    "Failed to fetch variable sets for project {}.",
-                    project_id
-                );
-                let error =
-                    res.body_string().await.map_err(surf_to_tool_error)?;
-                return Err(ToolError::General(anyhow::anyhow!(error)));
-            }
-        }
-        Err(e) => {
-            return Err(ToolError::General(anyhow::anyhow!(e)));
-        }
-    };
-    // Need to check pagination
-    if let Some(meta) = var_set_list.meta.clone() {
-        check_pagination(meta, &mut var_set_list, url, config, client).await?;
-    }
+    info!("Retrieving the list of variable sets for project {}.", project_id);
+    let url = Url::parse(&format!(
+        "{}/projects/{}/varsets",
+        config.base_url, project_id
+    ))?;
+    let var_sets = Paginator::new(url, config, client)?.collect().await?;
     info!("Finished retrieving variable sets.");
-    Ok(var_set_list)
+    Ok(VarSets::new(var_sets))
 }
 
 pub async fn create(
@@ -357,7 +229,7 @@ pub async fn create(
 ) -> Result<(), ToolError> {
     let url = Url::parse(&format!(
         "{}/organizations/{}/varsets",
-        BASE_URL, config.org
+        config.base_url, config.org
     ))?;
     let req = build_request(
         Method::Post,
@@ -370,8 +242,7 @@ pub async fn create(
         info!("Successfully created variable set");
     } else {
         error!("Failed to create variable set");
-        let error = res.body_string().await.map_err(surf_to_tool_error)?;
-        return Err(ToolError::General(anyhow::anyhow!(error)));
+        return Err(response_to_tool_error(&mut res).await);
     }
     Ok(())
 }
@@ -384,7 +255,7 @@ pub async fn apply_workspace(
 ) -> Result<(), ToolError> {
     let url = Url::parse(&format!(
         "{}/varsets/{}/relationships/workspaces",
-        BASE_URL, variable_set_id
+        config.base_url, variable_set_id
     ))?;
     let req = build_request(
         Method::Post,
@@ -397,8 +268,7 @@ pub async fn apply_workspace(
         info!("Successfully applied workspaces to variable set");
     } else {
         error!("Failed to apply workspaces to variable set");
-        let error = res.body_string().await.map_err(surf_to_tool_error)?;
-        return Err(ToolError::General(anyhow::anyhow!(error)));
+        return Err(response_to_tool_error(&mut res).await);
     }
     Ok(())
 }
@@ -411,7 +281,7 @@ pub async fn remove_workspace(
 ) -> Result<(), ToolError> {
     let url = Url::parse(&format!(
         "{}/varsets/{}/relationships/workspaces",
-        BASE_URL, variable_set_id
+        config.base_url, variable_set_id
     ))?;
     let req = build_request(
         Method::Delete,
@@ -424,8 +294,7 @@ pub async fn remove_workspace(
         info!("Successfully removed workspace from variable set");
     } else {
         error!("Failed to remove workspace from variable set");
-        let error = res.body_string().await.map_err(surf_to_tool_error)?;
-        return Err(ToolError::General(anyhow::anyhow!(error)));
+        return Err(response_to_tool_error(&mut res).await);
     }
     Ok(())
 }
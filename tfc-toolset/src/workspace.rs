@@ -1,14 +1,18 @@
 use crate::{
     build_request,
-    error::{surf_to_tool_error, ToolError},
-    filter, set_page_number,
+    error::{response_to_tool_error, surf_to_tool_error, ToolError},
+    filter, run,
     settings::{Core, Operators, Query, Tag},
-    tag, variable, variable_set, Meta, BASE_URL,
+    tag, variable, variable_set, Paginator,
 };
 use log::{error, info};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::{fmt::Display, vec};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    vec,
+};
 use surf::{http::Method, Client};
 use time::OffsetDateTime;
 use url::Url;
@@ -45,12 +49,106 @@ pub struct WorkspaceVariableSets {
 pub struct Workspace {
     pub id: String,
     pub attributes: Attributes,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relationships: Option<WorkspaceRelationships>,
+    /// The most recent run, populated from the included resources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_run: Option<LatestRun>,
+    /// Related resources side-loaded with the `include` parameter, these can
+    /// hold variable values so they are never written to output
+    #[serde(default, skip_serializing)]
+    pub included: Option<Included>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WorkspaceRelationships {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_run: Option<run::RelationshipOuter>,
+    /// Every other relationship, kept as is
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-struct Workspaces {
-    pub data: Vec<Workspace>,
-    pub meta: Option<Meta>,
+/// The resources related to a workspace that were side-loaded in a response
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Included {
+    /// The relationships that were requested, e.g. `vars`
+    pub include: Vec<String>,
+    /// The JSON:API resources related to the workspace
+    pub resources: Vec<Value>,
+}
+
+impl Included {
+    /// Whether a relationship was requested, the response may still leave
+    /// its resources out
+    pub fn is_included(&self, relationship: &str) -> bool {
+        self.include.iter().any(|i| i == relationship)
+    }
+
+    /// The resources of the given type, any that fail to parse are skipped
+    pub fn resources_of<T: DeserializeOwned>(
+        &self,
+        resource_type: &str,
+    ) -> Vec<T> {
+        self.resources
+            .iter()
+            .filter(|resource| resource["type"] == resource_type)
+            .filter_map(|resource| {
+                serde_json::from_value(resource.clone()).ok()
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LatestRunAttributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<run::Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The latest run of a workspace, as included alongside the workspace
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LatestRun {
+    pub id: String,
+    pub attributes: LatestRunAttributes,
+}
+
+impl Workspace {
+    /// The id of the latest run from the relationships, if there is one
+    pub fn latest_run_id(&self) -> Option<String> {
+        self.relationships
+            .as_ref()
+            .and_then(|r| r.latest_run.as_ref())
+            .and_then(|r| r.data.as_ref())
+            .map(|d| d.id.clone())
+    }
+
+    /// The variables of the workspace, if they were included
+    ///
+    /// The response only lists the variables in `relationships.vars.data`
+    /// when it loaded them, so without it, or when any variable listed there
+    /// is missing, `None` is returned and the variables have to be retrieved.
+    pub fn included_vars(&self) -> Option<Vec<variable::Variable>> {
+        let included =
+            self.included.as_ref().filter(|i| i.is_included("vars"))?;
+        let data = self.relationships.as_ref()?.other.get("vars")?["data"]
+            .as_array()?;
+        let vars: Vec<variable::Variable> = included.resources_of("vars");
+        let mut related = Vec::with_capacity(data.len());
+        for linkage in data {
+            let id = linkage["id"].as_str()?;
+            related.push(
+                vars.iter().find(|v| v.id.as_deref() == Some(id))?.clone(),
+            );
+        }
+        Some(related)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -192,6 +290,10 @@ pub struct Attributes {
     pub relationships: Option<Relationships>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tag_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
 impl Default for Attributes {
@@ -218,6 +320,8 @@ impl Default for Attributes {
             working_directory: None,
             relationships: None,
             tag_names: None,
+            locked: None,
+            updated_at: None,
         }
     }
 }
@@ -248,6 +352,8 @@ impl WorkspaceOuter {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct WorkspaceResponseOuter {
     pub data: Workspace,
+    #[serde(default)]
+    pub included: Vec<Value>,
 }
 
 async fn send_show_req(
@@ -255,6 +361,11 @@ async fn send_show_req(
     config: &Core,
     client: Client,
 ) -> Result<Workspace, ToolError> {
+    let include = include_param(config);
+    let url = Url::parse_with_params(
+        url.as_str(),
+        &[("include", include.join(","))],
+    )?;
     let req = build_request(Method::Get, url, config, None);
     match client.send(req).await {
         Ok(mut r) => {
@@ -264,12 +375,13 @@ async fn send_show_req(
                     .body_json::<WorkspaceResponseOuter>()
                     .await
                     .map_err(surf_to_tool_error)?;
-                Ok(res.data)
+                let mut workspace = res.data;
+                let index = IncludedIndex::new(&res.included);
+                attach_included(&mut workspace, &include, &index);
+                Ok(workspace)
             } else {
                 error!("Failed to retrieve workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -282,7 +394,10 @@ pub async fn show(
     client: Client,
 ) -> Result<Workspace, ToolError> {
     info!("Retrieving workspace {}.", workspace_id);
-    let url = Url::parse(&format!("{}/workspaces/{}", BASE_URL, workspace_id))?;
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}",
+        config.base_url, workspace_id
+    ))?;
     send_show_req(url, config, client).await
 }
 
@@ -294,7 +409,7 @@ pub async fn show_by_name(
     info!("Retrieving workspace {}.", workspace_name);
     let url = Url::parse(&format!(
         "{}/organizations/{}/workspaces/{}",
-        BASE_URL, config.org, workspace_name
+        config.base_url, config.org, workspace_name
     ))?;
     send_show_req(url, config, client).await
 }
@@ -308,7 +423,7 @@ pub async fn create(
     info!("Creating workspace {}.", name);
     let url = Url::parse(&format!(
         "{}/organizations/{}/workspaces/",
-        BASE_URL, config.org
+        config.base_url, config.org
     ))?;
     let req = build_request(
         Method::Post,
@@ -327,9 +442,7 @@ pub async fn create(
                 Ok(res.data)
             } else {
                 error!("Failed to create workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -343,7 +456,10 @@ pub async fn update(
     client: Client,
 ) -> Result<Workspace, ToolError> {
     info!("Updating workspace {}.", workspace_id);
-    let url = Url::parse(&format!("{}/workspaces/{}", BASE_URL, workspace_id))?;
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}",
+        config.base_url, workspace_id
+    ))?;
     let req = build_request(
         Method::Patch,
         url,
@@ -361,9 +477,7 @@ pub async fn update(
                 Ok(res.data)
             } else {
                 error!("Failed to update workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -379,7 +493,7 @@ pub async fn update_by_name(
     info!("Updating workspace {}.", workspace_name);
     let url = Url::parse(&format!(
         "{}/organizations/{}/workspaces/{}",
-        BASE_URL, config.org, workspace_name
+        config.base_url, config.org, workspace_name
     ))?;
     let req = build_request(
         Method::Patch,
@@ -398,9 +512,7 @@ pub async fn update_by_name(
                 Ok(res.data)
             } else {
                 error!("Failed to update workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -414,8 +526,10 @@ pub async fn delete(
     client: Client,
 ) -> Result<(), ToolError> {
     info!("Deleting workspace {}.", workspace_id);
-    let mut url =
-        Url::parse(&format!("{}/workspaces/{}", BASE_URL, workspace_id))?;
+    let mut url = Url::parse(&format!(
+        "{}/workspaces/{}",
+        config.base_url, workspace_id
+    ))?;
     let mut method = Method::Delete;
     if safe_delete {
         url = Url::parse(&format!("{}/actions/safe-delete", url))?;
@@ -429,9 +543,7 @@ pub async fn delete(
                 Ok(())
             } else {
                 error!("Failed to delete workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
@@ -447,7 +559,7 @@ pub async fn delete_by_name(
     info!("Deleting workspace {}.", workspace_name);
     let mut url = Url::parse(&format!(
         "{}/organizations/{}/workspaces/{}",
-        BASE_URL, config.org, workspace_name
+        config.base_url, config.org, workspace_name
     ))?;
     let mut method = Method::Delete;
     if safe_delete {
@@ -462,30 +574,185 @@ pub async fn delete_by_name(
                 Ok(())
             } else {
                 error!("Failed to delete workspace :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                Err(ToolError::General(anyhow::anyhow!(error)))
+                Err(response_to_tool_error(&mut r).await)
             }
         }
         Err(e) => Err(ToolError::General(e.into_inner())),
     }
 }
 
+async fn send_lock_req(
+    workspace_id: &str,
+    action: &str,
+    body: Option<serde_json::Value>,
+    config: &Core,
+    client: Client,
+) -> Result<Workspace, ToolError> {
+    let url = Url::parse(&format!(
+        "{}/workspaces/{}/actions/{}",
+        config.base_url, workspace_id, action
+    ))?;
+    let req = build_request(Method::Post, url, config, body);
+    match client.send(req).await {
+        Ok(mut r) => {
+            if r.status().is_success() {
+                info!("Successfully performed {} on workspace!", action);
+                let res = r
+                    .body_json::<WorkspaceResponseOuter>()
+                    .await
+                    .map_err(surf_to_tool_error)?;
+                Ok(res.data)
+            } else {
+                error!("Failed to {} workspace :(", action);
+                Err(response_to_tool_error(&mut r).await)
+            }
+        }
+        Err(e) => Err(ToolError::General(e.into_inner())),
+    }
+}
+
+/// Lock a workspace, a workspace that is already locked will return a
+/// conflict error
+pub async fn lock(
+    workspace_id: &str,
+    reason: Option<String>,
+    config: &Core,
+    client: Client,
+) -> Result<Workspace, ToolError> {
+    info!("Locking workspace {}.", workspace_id);
+    let body = reason.map(|reason| json!({ "reason": reason }));
+    send_lock_req(workspace_id, "lock", body, config, client).await
+}
+
+/// Unlock a workspace that was locked by the current token
+pub async fn unlock(
+    workspace_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Workspace, ToolError> {
+    info!("Unlocking workspace {}.", workspace_id);
+    send_lock_req(workspace_id, "unlock", None, config, client).await
+}
+
+/// Unlock a workspace regardless of who locked it, requires admin access
+pub async fn force_unlock(
+    workspace_id: &str,
+    config: &Core,
+    client: Client,
+) -> Result<Workspace, ToolError> {
+    info!("Force unlocking workspace {}.", workspace_id);
+    send_lock_req(workspace_id, "force-unlock", None, config, client).await
+}
+
+// The relationships to request, the latest run is always included so it can
+// be used in queries
+fn include_param(config: &Core) -> Vec<String> {
+    let mut include = vec!["latest_run".to_string()];
+    for relationship in config.workspaces.include.iter().flatten() {
+        if !include.contains(relationship) {
+            include.push(relationship.clone());
+        }
+    }
+    include
+}
+
+// The (type, id) pairs referenced by a JSON:API relationships object
+fn linkages(relationships: &Value) -> Vec<(&str, &str)> {
+    relationships
+        .as_object()
+        .into_iter()
+        .flat_map(|relationships| relationships.values())
+        .flat_map(|relationship| match &relationship["data"] {
+            Value::Array(items) => items.iter().collect(),
+            item => vec![item],
+        })
+        .filter_map(|data| Some((data["type"].as_str()?, data["id"].as_str()?)))
+        .collect()
+}
+
+// The included resources of a response, indexed so each workspace can find
+// the resources related to it without scanning all of them. A resource is
+// related if the workspace references it, or it references the workspace, as
+// variables do through `configurable`.
+struct IncludedIndex<'a> {
+    included: &'a [Value],
+    by_key: HashMap<(&'a str, &'a str), usize>,
+    by_workspace: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> IncludedIndex<'a> {
+    fn new(included: &'a [Value]) -> Self {
+        let mut by_key = HashMap::new();
+        let mut by_workspace: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, resource) in included.iter().enumerate() {
+            if let (Some(resource_type), Some(id)) =
+                (resource["type"].as_str(), resource["id"].as_str())
+            {
+                by_key.insert((resource_type, id), i);
+            }
+            for (resource_type, id) in linkages(&resource["relationships"]) {
+                if resource_type == "workspaces" {
+                    by_workspace.entry(id).or_default().push(i);
+                }
+            }
+        }
+        Self { included, by_key, by_workspace }
+    }
+
+    // The resources related to the workspace, in the order they were included
+    fn related(&self, workspace: &Workspace) -> Vec<Value> {
+        let relationships =
+            serde_json::to_value(&workspace.relationships).unwrap_or_default();
+        let mut positions: Vec<usize> = linkages(&relationships)
+            .into_iter()
+            .filter_map(|key| self.by_key.get(&key).copied())
+            .chain(
+                self.by_workspace
+                    .get(workspace.id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .copied(),
+            )
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions.into_iter().map(|i| self.included[i].clone()).collect()
+    }
+}
+
+// Attach the included resources related to the workspace, and its latest run
+fn attach_included(
+    workspace: &mut Workspace,
+    include: &[String],
+    index: &IncludedIndex,
+) {
+    let resources = index.related(workspace);
+    let included = Included { include: include.to_vec(), resources };
+    if let Some(run_id) = workspace.latest_run_id() {
+        workspace.latest_run = included
+            .resources_of::<LatestRun>("runs")
+            .into_iter()
+            .find(|run| run.id == run_id);
+    }
+    workspace.included = Some(included);
+}
+
 pub async fn list(
     filter: bool,
     config: &Core,
     client: Client,
 ) -> Result<Vec<Workspace>, ToolError> {
     info!("Retrieving the initial list of workspaces.");
-    let mut params = vec![
-        ("page[size]", config.pagination.page_size.clone()),
-        ("page[number]", config.pagination.start_page.clone()),
-    ];
+    let include = include_param(config);
+    let mut params = vec![("include", include.join(","))];
     if let Some(project) = config.project.clone() {
         params.push(("filter[project][id]", project))
     }
     let mut url = Url::parse_with_params(
-        &format!("{}/organizations/{}/workspaces/", BASE_URL, config.org),
+        &format!(
+            "{}/organizations/{}/workspaces/",
+            config.base_url, config.org
+        ),
         &params,
     )?;
     if filter {
@@ -545,87 +812,13 @@ pub async fn list(
             }
         }
     }
-    let req = build_request(Method::Get, url.clone(), config, None);
-    let mut workspaces: Vec<Workspace> = vec![];
-    let mut workspace_list: Workspaces = match client.send(req).await {
-        Ok(mut r) => {
-            if r.status().is_success() {
-                info!("Successfully retrieved workspaces!");
-                match r.body_json().await {
-                    Ok(r) => r,
-                    Err(e) => {
-                        error!("{:#?}", e);
-                        return Err(ToolError::General(anyhow::anyhow!(e)));
-                    }
-                }
-            } else {
-                error!("Failed to retrieve workspaces :(");
-                let error =
-                    r.body_string().await.map_err(surf_to_tool_error)?;
-                return Err(ToolError::General(anyhow::anyhow!(error)));
-            }
-        }
-        Err(e) => {
-            return Err(ToolError::General(anyhow::anyhow!(e)));
-        }
-    };
-    workspaces.append(&mut workspace_list.data);
-    // Need to check pagination
-    if let Some(meta) = workspace_list.meta {
-        let max_depth = config.pagination.max_depth.parse::<u32>()?;
-        if max_depth > 1 || max_depth == 0 {
-            let current_depth: u32 = 1;
-            if let Some(next_page) = meta.pagination.next_page {
-                if max_depth == 0 || current_depth < max_depth {
-                    let num_pages: u32 = if max_depth
-                        >= meta.pagination.total_pages
-                        || max_depth == 0
-                    {
-                        meta.pagination.total_pages
-                    } else {
-                        max_depth
-                    };
-
-                    // Get the next page and merge the result
-                    for n in next_page..=num_pages {
-                        let u = url.clone();
-                        info!("Retrieving workspaces page {}.", &n);
-                        let u = match set_page_number(n, u) {
-                            Some(u) => u,
-                            None => {
-                                error!("Failed to set page number.");
-                                return Err(ToolError::Pagination(
-                                    "Failed to set page number.".to_string(),
-                                ));
-                            }
-                        };
-                        let req =
-                            build_request(Method::Get, u.clone(), config, None);
-                        let mut response = client
-                            .send(req)
-                            .await
-                            .map_err(surf_to_tool_error)?;
-                        if response.status().is_success() {
-                            info!(
-                                "Successfully retrieved workspaces page {}!",
-                                &n
-                            );
-                            let mut ws = response
-                                .body_json::<Workspaces>()
-                                .await
-                                .map_err(surf_to_tool_error)?;
-                            workspaces.append(&mut ws.data);
-                        } else {
-                            let e = response
-                                .body_string()
-                                .await
-                                .map_err(surf_to_tool_error)?;
-                            error!("{:#?}", e);
-                        }
-                    }
-                }
-            }
-        }
+    let (mut workspaces, included): (Vec<Workspace>, _) =
+        Paginator::new(url, config, client.clone())?
+            .collect_with_included()
+            .await?;
+    let index = IncludedIndex::new(&included);
+    for workspace in &mut workspaces {
+        attach_included(workspace, &include, &index);
     }
     info!("Finished retrieving workspaces.");
     if filter {
@@ -636,11 +829,37 @@ pub async fn list(
                 filter::workspace::by_tag(&mut workspaces, config)?;
             }
 
-            if query.variables.is_some() {
-                // Get the variables for each workspace
-                let mut workspaces_variables =
-                    variable::list_batch(config, client, workspaces.clone())
-                        .await?;
+            // Filter the workspaces if query attributes have been provided
+            if query.attributes.is_some() {
+                info!("Filtering workspaces with attributes query.");
+                filter::workspace::by_attribute(&mut workspaces, config)?;
+            }
+
+            let needs_variables = query.variables.is_some()
+                || query.expr.as_ref().is_some_and(|e| e.has_variables());
+            if needs_variables || query.expr.is_some() {
+                // Get the variables for each workspace, only when a query
+                // needs them
+                let mut workspaces_variables = if needs_variables {
+                    let batch = variable::list_batch(
+                        config,
+                        client,
+                        workspaces.clone(),
+                    )
+                    .await;
+                    // A workspace without variables can't be matched, so
+                    // report every failure rather than return partial results
+                    batch.log_failures();
+                    batch.into_result()?
+                } else {
+                    workspaces
+                        .iter()
+                        .map(|ws| WorkspaceVariables {
+                            workspace: ws.clone(),
+                            variables: vec![],
+                        })
+                        .collect()
+                };
                 // Filter the workspaces if query variables have been provided
                 if query.variables.is_some() {
                     info!("Filtering workspaces with variable query.");
@@ -649,6 +868,14 @@ pub async fn list(
                         config,
                     )?;
                 }
+                // Filter the workspaces if a query expression has been provided
+                if query.expr.is_some() {
+                    info!("Filtering workspaces with query expression.");
+                    filter::workspace::by_expr(
+                        &mut workspaces_variables,
+                        config,
+                    )?;
+                }
 
                 workspaces.clear();
                 for ws in &workspaces_variables {
@@ -659,3 +886,110 @@ pub async fn list(
     }
     Ok(workspaces)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: &str, run_id: &str, var_ids: &[&str]) -> Workspace {
+        let vars: Vec<Value> = var_ids
+            .iter()
+            .map(|var_id| json!({ "type": "vars", "id": var_id }))
+            .collect();
+        serde_json::from_value(json!({
+            "id": id,
+            "attributes": { "name": id },
+            "relationships": {
+                "latest-run": { "data": { "type": "runs", "id": run_id } },
+                "project": { "data": { "type": "projects", "id": "prj-1" } },
+                "vars": { "data": vars }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn var(id: &str, workspace_id: &str) -> Value {
+        json!({
+            "type": "vars",
+            "id": id,
+            "attributes": { "key": id, "value": "secret", "category": "env" },
+            "relationships": {
+                "configurable": {
+                    "data": { "type": "workspaces", "id": workspace_id }
+                }
+            }
+        })
+    }
+
+    fn ids(resources: &[Value]) -> Vec<&str> {
+        resources.iter().map(|r| r["id"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn attach_related_resources() {
+        let included = vec![
+            json!({ "type": "runs", "id": "run-1", "attributes": { "status": "applied" } }),
+            json!({ "type": "runs", "id": "run-2", "attributes": { "status": "errored" } }),
+            json!({ "type": "projects", "id": "prj-1", "attributes": {} }),
+            var("var-1", "ws-1"),
+            var("var-2", "ws-2"),
+            var("var-3", "ws-1"),
+        ];
+        let include = vec!["latest_run".to_string(), "vars".to_string()];
+        let index = IncludedIndex::new(&included);
+        let mut ws1 = workspace("ws-1", "run-1", &["var-1", "var-3"]);
+        let mut ws2 = workspace("ws-2", "run-2", &["var-2"]);
+        attach_included(&mut ws1, &include, &index);
+        attach_included(&mut ws2, &include, &index);
+        let resources = &ws1.included.as_ref().unwrap().resources;
+        assert_eq!(ids(resources), ["run-1", "prj-1", "var-1", "var-3"]);
+        let resources = &ws2.included.as_ref().unwrap().resources;
+        assert_eq!(ids(resources), ["run-2", "prj-1", "var-2"]);
+        assert_eq!(ws1.latest_run.as_ref().unwrap().id, "run-1");
+        assert_eq!(
+            ws2.latest_run.unwrap().attributes.status,
+            Some(run::Status::Errored)
+        );
+        let vars = ws1.included_vars().unwrap();
+        assert_eq!(vars.len(), 2);
+    }
+
+    #[test]
+    fn included_resources_are_not_serialized() {
+        let included = vec![var("var-1", "ws-1")];
+        let mut ws = workspace("ws-1", "run-1", &["var-1"]);
+        let include = vec!["vars".to_string()];
+        attach_included(&mut ws, &include, &IncludedIndex::new(&included));
+        let output = serde_json::to_string(&ws).unwrap();
+        assert!(!output.contains("secret"));
+        assert!(!output.contains("included"));
+    }
+
+    #[test]
+    fn vars_requested_but_not_returned() {
+        let include = vec!["vars".to_string()];
+        // The response left out the vars relationship entirely
+        let mut ws: Workspace = serde_json::from_value(json!({
+            "id": "ws-1",
+            "attributes": { "name": "ws-1" },
+            "relationships": {}
+        }))
+        .unwrap();
+        attach_included(&mut ws, &include, &IncludedIndex::new(&[]));
+        assert!(ws.included_vars().is_none());
+        // The vars are listed but not all of them were included
+        let included = vec![var("var-1", "ws-1")];
+        let mut ws = workspace("ws-1", "run-1", &["var-1", "var-2"]);
+        attach_included(&mut ws, &include, &IncludedIndex::new(&included));
+        assert!(ws.included_vars().is_none());
+        // No vars at all is only trusted when the response says so
+        let mut ws = workspace("ws-1", "run-1", &[]);
+        attach_included(&mut ws, &include, &IncludedIndex::new(&[]));
+        assert_eq!(ws.included_vars().unwrap().len(), 0);
+        // Without asking for vars they are never used
+        let mut ws = workspace("ws-1", "run-1", &["var-1"]);
+        let include = vec!["latest_run".to_string()];
+        attach_included(&mut ws, &include, &IncludedIndex::new(&included));
+        assert!(ws.included_vars().is_none());
+    }
+}
@@ -0,0 +1,4 @@
+pub(super) const INFO: &str =
+    "Show the location, mode and size of the HTTP cache";
+
+pub(super) const CLEAR: &str = "Remove every response from the HTTP cache";
@@ -0,0 +1,15 @@
+use crate::error::ArgError;
+
+use tfc_toolset::settings::Core;
+use tfc_toolset_extras::cache;
+
+pub async fn clear(core: &Core) -> miette::Result<(), ArgError> {
+    let cache_info = cache::info(&core.cache)?;
+    cache::clear(&core.cache).await?;
+    println!(
+        "Removed {} entries from {}",
+        cache_info.entries,
+        cache_info.dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use crate::error::ArgError;
+
+use tfc_toolset::settings::Core;
+use tfc_toolset_extras::{cache, CacheInfo};
+
+pub fn info(core: &Core) -> miette::Result<CacheInfo, ArgError> {
+    let cache_info = cache::info(&core.cache)?;
+    println!("Directory: {}", cache_info.dir.display());
+    println!("Mode: {}", core.cache.mode);
+    println!("TTL heuristic: {}", core.cache.ttl_heuristic);
+    println!("Entries: {}", cache_info.entries);
+    println!("Size: {}", format_size(cache_info.size));
+    Ok(cache_info)
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
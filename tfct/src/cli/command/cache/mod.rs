@@ -0,0 +1,23 @@
+mod about;
+
+pub(crate) mod clear;
+pub(crate) mod info;
+
+pub use clear::clear;
+pub use info::info;
+
+use clap::{Args, Subcommand};
+
+#[derive(Args, Debug)]
+pub(crate) struct Commands {
+    #[command(subcommand)]
+    pub command: CacheCmds,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum CacheCmds {
+    #[clap(about = about::INFO)]
+    Info,
+    #[clap(about = about::CLEAR)]
+    Clear,
+}
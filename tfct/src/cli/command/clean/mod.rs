@@ -135,8 +135,8 @@ fn build_path(config: &Settings, vcs: &VcsRepo, url: Url) -> String {
     let mut id = match vcs.identifier.clone() {
         Some(i) => i,
         None => {
-            let segments = url.path_segments().unwrap();
-            segments.last().unwrap().to_string()
+            let mut segments = url.path_segments().unwrap();
+            segments.next_back().unwrap().to_string()
         }
     };
     if let Some(branch) = &vcs.branch {
@@ -114,9 +114,13 @@ async fn cleanup_workspaces(
     if config.cleanup.unlisted_variables || config.cleanup.missing_repositories
     {
         // Get the variables for each workspace
-        let workspaces_variables =
+        let batch =
             variable::list_batch(core, client.clone(), workspaces.clone())
-                .await?;
+                .await;
+        // Report every workspace that failed before giving up, a partial
+        // cleanup would look like a complete one
+        batch.log_failures();
+        let workspaces_variables = batch.into_result()?;
         info!("Cloning workspace repositories.");
         let process_results = process(config, &workspaces_variables)?;
         for entry in &workspaces_variables {
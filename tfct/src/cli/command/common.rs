@@ -5,7 +5,7 @@ use tfc_toolset::{
     settings::Core,
     workspace::{self, Workspace},
 };
-use tfc_toolset_extras::WorkspacesFile;
+use tfc_toolset_extras::{parse_workspace_name, WorkspacesFile};
 
 const WORKSPACE_NAME: &str = "The name of the workspace";
 
@@ -108,31 +108,58 @@ pub(crate) async fn parse_workspace_file(
     let mut workspaces = vec![];
     if let Some(workspace_entries) = workspace_file.workspaces {
         for workspace_entry in workspace_entries {
-            if workspace_entry.id.is_some()
-                && workspace_entry.attributes.is_some()
-            {
-                let workspace = Workspace {
-                    id: workspace_entry.id.unwrap(),
-                    attributes: workspace_entry.attributes.unwrap(),
-                };
-                workspaces.push(workspace);
-            } else if workspace_entry.id.is_some() {
-                let workspace_id = &workspace_entry.id.unwrap();
-                let workspace =
-                    workspace::show(workspace_id, config, client.clone())
-                        .await?;
-                workspaces.push(workspace);
-            } else if workspace_entry.name.is_some() {
-                let workspace_name = &workspace_entry.name.unwrap();
-                let workspace = workspace::show_by_name(
-                    workspace_name,
-                    config,
-                    client.clone(),
-                )
-                .await?;
-                workspaces.push(workspace);
+            match (
+                workspace_entry.id,
+                workspace_entry.attributes,
+                workspace_entry.name,
+            ) {
+                (Some(id), Some(attributes), _) => {
+                    workspaces.push(Workspace {
+                        id,
+                        attributes,
+                        relationships: None,
+                        latest_run: None,
+                        included: None,
+                    });
+                }
+                (Some(workspace_id), None, _) => {
+                    let workspace =
+                        workspace::show(&workspace_id, config, client.clone())
+                            .await?;
+                    workspaces.push(workspace);
+                }
+                (None, _, Some(workspace_name)) => {
+                    let workspace = workspace::show_by_name(
+                        &workspace_name,
+                        config,
+                        client.clone(),
+                    )
+                    .await?;
+                    workspaces.push(workspace);
+                }
+                _ => {}
             }
         }
     }
     Ok(workspaces)
 }
+
+/// Resolve the workspaces selected by the given arguments
+pub(crate) async fn resolve_workspaces(
+    args: &WorkspaceArgs,
+    config: &Core,
+    client: Client,
+) -> Result<Vec<Workspace>, ArgError> {
+    check_workspace_identifier(args)?;
+    let workspaces = if let Some(workspace_name) = &args.workspace_name {
+        parse_workspace_name(workspace_name)?;
+        vec![workspace::show_by_name(workspace_name, config, client).await?]
+    } else if let Some(workspace_id) = &args.workspace_id {
+        vec![workspace::show(workspace_id, config, client).await?]
+    } else if let Some(file_path) = &args.workspace_file {
+        parse_workspace_file(file_path, config, client).await?
+    } else {
+        workspace::list(true, config, client).await?
+    };
+    Ok(workspaces)
+}
@@ -0,0 +1,9 @@
+pub(super) const SHOW: &str =
+    "Show the effective configuration after merging every source";
+
+pub(super) const INIT: &str =
+    "Write a commented starter settings file covering every setting";
+
+pub(super) const PATH: &str = "Where to write the settings file, defaults to \
+    $XDG_CONFIG_HOME/tfct/config.toml";
+pub(super) const FORCE: &str = "Overwrite the settings file if it exists";
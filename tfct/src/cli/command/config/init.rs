@@ -0,0 +1,27 @@
+use super::InitArgs;
+use crate::error::ArgError;
+
+use log::info;
+use std::{fs, path::PathBuf};
+use tfc_toolset::{error::ToolError, settings};
+
+// Every setting commented out with its default value
+const TEMPLATE: &str = include_str!("template.toml");
+
+pub fn init(args: &InitArgs) -> miette::Result<PathBuf, ArgError> {
+    let path = match &args.path {
+        Some(path) => path.clone(),
+        None => settings::user_config_file()
+            .unwrap_or_else(|| PathBuf::from("settings.toml")),
+    };
+    if path.exists() && !args.force {
+        return Err(ArgError::SettingsFileExists(path.display().to_string()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(ToolError::Io)?;
+    }
+    fs::write(&path, TEMPLATE).map_err(ToolError::Io)?;
+    info!("Successfully wrote settings file to {}!", path.display());
+    println!("{}", path.display());
+    Ok(path)
+}
@@ -0,0 +1,32 @@
+mod about;
+
+pub(crate) mod init;
+pub(crate) mod show;
+
+pub use init::init;
+pub use show::show;
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub(crate) struct Commands {
+    #[command(subcommand)]
+    pub command: ConfigCmds,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ConfigCmds {
+    #[clap(about = about::SHOW)]
+    Show,
+    #[clap(about = about::INIT)]
+    Init(InitArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    #[arg(short, long, help = about::PATH)]
+    pub path: Option<PathBuf>,
+    #[arg(action, long, help = about::FORCE, default_value = "false")]
+    pub force: bool,
+}
@@ -0,0 +1,20 @@
+use crate::{error::ArgError, settings::Settings};
+
+use tfc_toolset::{credentials::TokenSource, settings::Core};
+
+const MASK: &str = "********";
+
+pub fn show(core: &Core, config: &Settings) -> miette::Result<(), ArgError> {
+    // Never print the token itself, only whether one was set. The other
+    // token sources are not resolved, so a failing `token_command` is shown
+    // as configured rather than run
+    let mut core = core.clone();
+    if !core.token.is_empty() {
+        core.token = MASK.to_string();
+        core.token_source = Some(TokenSource::Config);
+    }
+    let mut merged = toml::Table::try_from(&core)?;
+    merged.extend(toml::Table::try_from(config)?);
+    println!("{}", toml::to_string_pretty(&merged)?);
+    Ok(())
+}
@@ -1,5 +1,7 @@
+pub(crate) mod cache;
 pub(crate) mod clean;
 pub(crate) mod common;
+pub(crate) mod config;
 pub(crate) mod run;
 pub(crate) mod tag;
 pub(crate) mod variable;
@@ -2,9 +2,16 @@ pub(super) const STATUS: &str = "Get the status of a run.";
 pub(super) const SPEC: &str = "Queue up speculative plan runs";
 pub(super) const PLAN: &str = "Queue up plan and apply runs";
 pub(super) const CREATE: &str = "Create a run.";
+pub(super) const APPLY: &str = "Apply a run that is waiting for confirmation.";
 pub(super) const CANCEL: &str = "Cancel a run.";
 pub(super) const DISCARD: &str = "Discard a run.";
+pub(super) const SUMMARIZE_CHANGES: &str = "Whether to download the plan \
+    JSON of each run and summarize the resource changes";
+pub(super) const LOGS: &str = "Print or save the plan or apply log of a run.";
 pub(super) const RUN_ID: &str = "The id of the run.";
+pub(super) const RESULTS_FILE: &str = "A file containing saved run results, \
+    every run with a status of planned_and_saved will be applied";
+pub(super) const COMMENT: &str = "A comment to include with the action";
 pub(super) const MESSAGE: &str = "A message to include with the run";
 pub(super) const TARGET_ADDRS: &str =
     "A list of resource addresses to target for the run";
@@ -32,3 +39,11 @@ pub(super) const STATUS_CHECK_SLEEP_SECONDS: &str = "The number of seconds to \
     wait between checking the status of a run";
 pub(super) const CANCEL_ON_TIMEOUT: &str = "Whether to cancel the run if it \
     reaches the configured limits";
+pub(super) const APPLY_LOG: &str =
+    "Retrieve the apply log instead of the plan log";
+pub(super) const FOLLOW: &str =
+    "Follow the log while the run is in progress until it has finished";
+pub(super) const POLL_INTERVAL_SECONDS: &str = "The number of seconds to \
+    wait between checking for new log output when following";
+pub(super) const LOG_FILE: &str =
+    "Save the log to this file instead of printing it";
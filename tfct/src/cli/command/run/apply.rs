@@ -0,0 +1,81 @@
+use super::{about, plan::QueueRunResult};
+use crate::error::ArgError;
+
+use clap::Args;
+use log::{error, info, warn};
+use std::path::PathBuf;
+use surf::Client;
+use tfc_toolset::{run::Status, settings::Core};
+
+#[derive(Args, Debug)]
+pub struct ApplyArgs {
+    #[arg(short = 'i', long, help = about::RUN_ID, conflicts_with = "results_file")]
+    pub run_id: Option<String>,
+    #[arg(short = 'r', long, help = about::RESULTS_FILE, conflicts_with = "run_id")]
+    pub results_file: Option<PathBuf>,
+    #[arg(short = 'c', long, help = about::COMMENT)]
+    pub comment: Option<String>,
+}
+
+pub async fn apply(
+    args: &ApplyArgs,
+    config: &Core,
+    client: Client,
+) -> miette::Result<(), ArgError> {
+    if let Some(run_id) = &args.run_id {
+        tfc_toolset::run::apply(
+            run_id,
+            args.comment.clone(),
+            config,
+            client.clone(),
+        )
+        .await?;
+    } else if let Some(results_file) = &args.results_file {
+        let queue_run_result = QueueRunResult::load(results_file)?;
+        let saved_status = Status::PlannedAndSaved.to_string();
+        let runs: Vec<_> = queue_run_result
+            .results
+            .iter()
+            .filter(|result| result.status == saved_status)
+            .collect();
+        if runs.is_empty() {
+            warn!(
+                "No runs in {} are {}, nothing to apply.",
+                results_file.display(),
+                saved_status
+            );
+        }
+        let mut failed = 0;
+        for result in &runs {
+            info!(
+                "Applying run {} for workspace {}.",
+                result.id, result.workspace_id
+            );
+            if let Err(e) = tfc_toolset::run::apply(
+                &result.id,
+                args.comment.clone(),
+                config,
+                client.clone(),
+            )
+            .await
+            {
+                error!(
+                    "Failed to apply run {} for workspace {}: {}",
+                    result.id, result.workspace_id, e
+                );
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            return Err(ArgError::PartialFailure {
+                action: "apply",
+                items: "runs",
+                failed,
+                total: runs.len(),
+            });
+        }
+    } else {
+        return Err(ArgError::MissingRunIdentifier);
+    }
+    Ok(())
+}
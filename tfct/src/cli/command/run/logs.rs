@@ -0,0 +1,72 @@
+use super::about;
+use crate::error::ArgError;
+
+use clap::Args;
+use log::info;
+use std::{io::Write, path::PathBuf, time::Duration};
+use surf::Client;
+use tfc_toolset::{apply, error::ToolError, plan, run, settings::Core};
+
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    #[arg(short = 'i', long, help = about::RUN_ID)]
+    pub run_id: String,
+    #[arg(long, help = about::APPLY_LOG, action, default_value = "false")]
+    pub apply: bool,
+    #[arg(short = 'F', long, help = about::FOLLOW, action, default_value = "false")]
+    pub follow: bool,
+    #[arg(long, help = about::POLL_INTERVAL_SECONDS, default_value = "5")]
+    pub poll_interval_seconds: u64,
+    #[arg(long, help = about::LOG_FILE)]
+    pub log_file: Option<PathBuf>,
+}
+
+pub async fn logs(
+    args: &LogsArgs,
+    config: &Core,
+    client: Client,
+) -> miette::Result<(), ArgError> {
+    let run = run::status(&args.run_id, config, client.clone()).await?;
+    let id = if args.apply { run.apply_id() } else { run.plan_id() };
+    let id = id.ok_or_else(|| {
+        ToolError::General(anyhow::anyhow!(
+            "Run {} does not have {}",
+            args.run_id,
+            if args.apply { "an apply" } else { "a plan" }
+        ))
+    })?;
+    info!("Retrieving logs for run: {}", args.run_id);
+    let mut log = String::new();
+    if args.follow {
+        let poll_interval = Duration::from_secs(args.poll_interval_seconds);
+        let on_chunk = |chunk: &str| {
+            // Only stream to stdout when the log isn't being saved
+            if args.log_file.is_none() {
+                print!("{}", chunk);
+                let _ = std::io::stdout().flush();
+            }
+            log.push_str(chunk);
+        };
+        if args.apply {
+            apply::follow_logs(&id, poll_interval, config, client, on_chunk)
+                .await?;
+        } else {
+            plan::follow_logs(&id, poll_interval, config, client, on_chunk)
+                .await?;
+        }
+    } else {
+        log = if args.apply {
+            apply::logs(&id, config, client).await?
+        } else {
+            plan::logs(&id, config, client).await?
+        };
+        if args.log_file.is_none() {
+            print!("{}", log);
+        }
+    }
+    if let Some(path) = &args.log_file {
+        std::fs::write(path, &log).map_err(ToolError::Io)?;
+        info!("Log saved to: {}", path.display());
+    }
+    Ok(())
+}
@@ -1,12 +1,16 @@
 mod about;
+pub(crate) mod apply;
 pub(crate) mod cancel;
 pub(crate) mod discard;
+pub(crate) mod logs;
 pub(crate) mod plan;
 pub(crate) mod spec;
 pub(crate) mod status;
 
+pub use apply::{apply, ApplyArgs};
 pub use cancel::{cancel, CancelArgs};
 pub use discard::{discard, DiscardArgs};
+pub use logs::{logs, LogsArgs};
 pub use plan::plan;
 pub use spec::spec;
 pub use status::{status, StatusArgs};
@@ -29,10 +33,14 @@ pub(crate) enum RunCmds {
     Spec(DefaultArgs),
     #[clap(about = about::PLAN)]
     Plan(PlanArgs),
+    #[clap(about = about::APPLY)]
+    Apply(ApplyArgs),
     #[clap(about = about::CANCEL)]
     Cancel(CancelArgs),
     #[clap(about = about::DISCARD)]
     Discard(DiscardArgs),
+    #[clap(about = about::LOGS)]
+    Logs(LogsArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -77,6 +85,8 @@ pub struct DefaultArgs {
     pub queue_status_check_sleep_seconds: Option<u64>,
     #[arg(long, help = about::CANCEL_ON_TIMEOUT)]
     pub cancel_on_timeout: Option<bool>,
+    #[arg(long, help = about::SUMMARIZE_CHANGES)]
+    pub summarize_changes: Option<bool>,
 }
 
 fn set_default_args(args: &mut Attributes, default: &DefaultArgs) {
@@ -112,6 +122,9 @@ fn override_queue_options(options: &mut QueueOptions, default: &DefaultArgs) {
     if let Some(cancel_on_timeout) = default.cancel_on_timeout {
         options.cancel_on_timeout = cancel_on_timeout;
     }
+    if let Some(summarize_changes) = default.summarize_changes {
+        options.summarize_changes = summarize_changes;
+    }
 }
 
 fn set_apply_args(args: &mut Attributes, apply: &PlanArgs) {
@@ -15,6 +15,7 @@ use log::info;
 use surf::Client;
 use tfc_toolset::{
     error::ToolError,
+    plan::ChangeSummary,
     run::{work_queue, Attributes, QueueOptions, QueueResult, RunResult},
     settings::Core,
     workspace,
@@ -25,6 +26,8 @@ use tfc_toolset_extras::{parse_workspace_name, ExtrasError};
 pub struct QueueRunResult {
     pub results: Vec<RunResult>,
     pub errors: Vec<RunResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ChangeSummary>,
 }
 
 impl QueueRunResult {
@@ -52,7 +55,11 @@ impl QueueRunResult {
 
 impl From<QueueResult> for QueueRunResult {
     fn from(queue_result: QueueResult) -> Self {
-        Self { results: queue_result.results, errors: queue_result.errors }
+        Self {
+            results: queue_result.results,
+            errors: queue_result.errors,
+            summary: queue_result.summary,
+        }
     }
 }
 
@@ -76,11 +83,13 @@ pub async fn plan(
         .status_check_sleep_seconds
         .unwrap_or(settings::STATUS_CHECK_SLEEP_SECONDS_DEFAULT);
     let cancel_on_timeout = config.run.cancel_on_timeout.unwrap_or(false);
+    let summarize_changes = config.run.summarize_changes.unwrap_or(false);
     let mut options = QueueOptions {
         max_concurrent,
         max_iterations,
         status_check_sleep_seconds,
         cancel_on_timeout,
+        summarize_changes,
     };
     override_queue_options(&mut options, &args.default);
     let mut attributes = Attributes::default();
@@ -36,11 +36,13 @@ pub async fn spec(
         .status_check_sleep_seconds
         .unwrap_or(settings::STATUS_CHECK_SLEEP_SECONDS_DEFAULT);
     let cancel_on_timeout = config.run.cancel_on_timeout.unwrap_or(false);
+    let summarize_changes = config.run.summarize_changes.unwrap_or(false);
     let mut options = QueueOptions {
         max_concurrent,
         max_iterations,
         status_check_sleep_seconds,
         cancel_on_timeout,
+        summarize_changes,
     };
     override_queue_options(&mut options, args);
     let mut attributes = Attributes {
@@ -3,9 +3,41 @@ pub(super) const CREATE: &str = "Create a new workspace variable";
 pub(super) const DELETE: &str = "Delete a workspace variable";
 
 pub(super) const LIST: &str = "List workspace variables";
+
+pub(super) const SET: &str =
+    "Create a workspace variable, or update it if the key already exists";
+pub(super) const SYNC: &str =
+    "Make workspace variables match a file, creating, updating and deleting as needed";
+pub(super) const SYNC_VAR_FILE: &str =
+    "A file containing the desired variables for the workspaces";
+pub(super) const CATEGORY: &str = "Only sync variables in this category, \
+    terraform or env, all categories are synced if omitted";
+pub(super) const AUTO_APPROVE: &str =
+    "Apply the changes without asking for confirmation";
+pub(super) const COPY: &str = "Copy variables from one workspace to others";
+pub(super) const FROM_WORKSPACE: &str =
+    "The name of the workspace to copy variables from";
+pub(super) const FROM_WORKSPACE_ID: &str =
+    "The id of the workspace to copy variables from";
+pub(super) const INCLUDE: &str = "Only copy variables with keys matching \
+    this regular expression, can be given more than once";
+pub(super) const EXCLUDE: &str = "Don't copy variables with keys matching \
+    this regular expression, can be given more than once";
+pub(super) const OVERWRITE: &str =
+    "Overwrite variables that already exist instead of skipping them";
 pub(super) const VARIABLE: &str = "The variable to apply to the workspace, \
     in the format of 'key=value:description:category:hcl:sensitive'";
 pub(super) const VAR_FILE: &str = "A file containing variables";
+pub(super) const TFVARS_FILE: &str = "A .tfvars file containing terraform \
+    variables, values that aren't strings, numbers or booleans are set as HCL";
+pub(super) const ENV_FILE: &str =
+    "A dotenv file containing environment variables";
+pub(super) const EXPORT_TFVARS_FILE: &str = "Export the terraform \
+    variables to this .tfvars file, prefixed with the workspace name when \
+    listing more than one workspace";
+pub(super) const EXPORT_ENV_FILE: &str = "Export the environment \
+    variables to this dotenv file, prefixed with the workspace name when \
+    listing more than one workspace";
 pub(super) const VARIABLE_KEY: &str =
     "The key of the variable to delete from the workspace";
 
@@ -0,0 +1,192 @@
+use super::about;
+use crate::{
+    cli::command::common::{resolve_workspaces, WorkspaceArgs},
+    error::ArgError,
+    settings::Settings,
+};
+
+use clap::Args;
+use log::{error, info, warn};
+use regex::Regex;
+use surf::Client;
+use tfc_toolset::{
+    settings::Core,
+    variable::{self, Variable},
+    workspace::{self, WorkspaceVariables},
+};
+use tfc_toolset_extras::{parse_workspace_name, VariablesFile};
+
+#[derive(Args, Debug)]
+pub struct CopyArgs {
+    #[arg(long, help = about::FROM_WORKSPACE, conflicts_with = "from_workspace_id")]
+    pub from_workspace: Option<String>,
+    #[arg(long, help = about::FROM_WORKSPACE_ID, conflicts_with = "from_workspace")]
+    pub from_workspace_id: Option<String>,
+    #[arg(long, help = about::INCLUDE, required = false)]
+    pub include: Vec<String>,
+    #[arg(long, help = about::EXCLUDE, required = false)]
+    pub exclude: Vec<String>,
+    #[arg(
+    long,
+    action,
+    help = about::OVERWRITE,
+    default_value = "false",
+    required = false
+    )]
+    pub overwrite: bool,
+    #[clap(flatten)]
+    default: WorkspaceArgs,
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>, ArgError> {
+    patterns.iter().map(|p| Regex::new(p).map_err(ArgError::from)).collect()
+}
+
+pub async fn copy(
+    args: &CopyArgs,
+    core: &Core,
+    config: &Settings,
+    client: Client,
+) -> miette::Result<Vec<WorkspaceVariables>, ArgError> {
+    let source = if let Some(workspace_name) = &args.from_workspace {
+        parse_workspace_name(workspace_name)?;
+        workspace::show_by_name(workspace_name, core, client.clone()).await?
+    } else if let Some(workspace_id) = &args.from_workspace_id {
+        workspace::show(workspace_id, core, client.clone()).await?
+    } else {
+        return Err(ArgError::MissingSourceWorkspace);
+    };
+    let include = compile(&args.include)?;
+    let exclude = compile(&args.exclude)?;
+    let source_vars: Vec<Variable> =
+        variable::list(&source.id, core, client.clone())
+            .await?
+            .into_iter()
+            .filter(|v| {
+                include.is_empty()
+                    || include.iter().any(|r| r.is_match(&v.attributes.key))
+            })
+            .filter(|v| !exclude.iter().any(|r| r.is_match(&v.attributes.key)))
+            .collect();
+    // Sensitive values are never returned by the API so they can't be copied
+    let (sensitive, source_vars): (Vec<Variable>, Vec<Variable>) = source_vars
+        .into_iter()
+        .partition(|v| v.attributes.sensitive.unwrap_or(false));
+
+    let workspaces =
+        resolve_workspaces(&args.default, core, client.clone()).await?;
+    let mut workspaces_variables = Vec::new();
+    let mut attempted = 0;
+    let mut failed = 0;
+    for workspace in workspaces.into_iter().filter(|ws| ws.id != source.id) {
+        info!("Copying variables to workspace {}.", workspace.id);
+        let existing =
+            match variable::list(&workspace.id, core, client.clone()).await {
+                Ok(existing) => existing,
+                Err(e) => {
+                    // None of the variables can be copied to this workspace
+                    error!(
+                        "Failed to retrieve variables for workspace {}: {}",
+                        workspace.id, e
+                    );
+                    attempted += source_vars.len();
+                    failed += source_vars.len();
+                    continue;
+                }
+            };
+        let mut entry = WorkspaceVariables {
+            workspace: workspace.clone(),
+            variables: vec![],
+        };
+        let mut skipped = vec![];
+        let mut skipped_sensitive = vec![];
+        for var in &source_vars {
+            let attributes = var.attributes.clone();
+            let found = variable::find(
+                &existing,
+                &attributes.key,
+                &attributes.category,
+            );
+            let result = match found {
+                Some(_) if !args.overwrite => {
+                    skipped.push(attributes.key);
+                    continue;
+                }
+                // Overwriting would try to make the target variable no
+                // longer sensitive, which the API may reject
+                Some(target)
+                    if target.attributes.sensitive.unwrap_or(false) =>
+                {
+                    skipped_sensitive.push(attributes.key);
+                    continue;
+                }
+                Some(Variable { id: Some(variable_id), .. }) => {
+                    variable::update(
+                        &workspace.id,
+                        variable_id,
+                        attributes,
+                        core,
+                        client.clone(),
+                    )
+                    .await
+                }
+                _ => {
+                    let new_var = Variable { id: None, ..var.clone() };
+                    variable::create(
+                        &workspace.id,
+                        new_var,
+                        core,
+                        client.clone(),
+                    )
+                    .await
+                }
+            };
+            attempted += 1;
+            match result {
+                Ok(copied) => entry.variables.push(copied),
+                Err(e) => {
+                    error!(
+                        "Failed to copy variable {} to workspace {}: {}",
+                        var.attributes.key, workspace.id, e
+                    );
+                    failed += 1;
+                }
+            }
+        }
+        if !skipped.is_empty() {
+            info!(
+                "Skipped existing variables on workspace {}: {:?}",
+                workspace.id, skipped
+            );
+        }
+        if !skipped_sensitive.is_empty() {
+            warn!(
+                "Skipped existing sensitive variables on workspace {}, they need to be updated manually: {:?}",
+                workspace.id, skipped_sensitive
+            );
+        }
+        workspaces_variables.push(entry);
+    }
+    if !sensitive.is_empty() {
+        let keys: Vec<&str> =
+            sensitive.iter().map(|v| v.attributes.key.as_str()).collect();
+        warn!(
+            "Sensitive variables were not copied and need to be set manually: {:?}",
+            keys
+        );
+    }
+    info!("{:#?}", &workspaces_variables);
+    if core.save_output {
+        VariablesFile::from(workspaces_variables.clone())
+            .save(&core.output, config.pretty_output)?;
+    }
+    if failed > 0 {
+        return Err(ArgError::PartialFailure {
+            action: "copy",
+            items: "variables",
+            failed,
+            total: attempted,
+        });
+    }
+    Ok(workspaces_variables)
+}
@@ -3,11 +3,10 @@ use crate::{
     cli::command::common::{check_workspace_identifier, parse_workspace_file},
     error::ArgError,
     settings::Settings,
-    variable::{check_variable_identifier_basic, parse_variable_file},
+    variable::{check_variable_identifier_basic, collect_variables},
 };
 
 use log::{debug, info};
-use std::str::FromStr;
 use surf::Client;
 use tfc_toolset::{
     settings::Core,
@@ -24,21 +23,7 @@ pub async fn create(
 ) -> miette::Result<Vec<WorkspaceVariables>, ArgError> {
     check_workspace_identifier(&args.default)?;
     check_variable_identifier_basic(args)?;
-    let mut vars: Option<Vec<variable::Variable>> = None;
-    for var_string in args.var.iter() {
-        let var = variable::Variable::from_str(var_string)?;
-        match vars {
-            Some(ref mut vars) => vars.push(var),
-            None => vars = Some(vec![var]),
-        }
-    }
-    if let Some(variables_file) = &args.var_file {
-        let variables = parse_variable_file(variables_file).await?;
-        match vars {
-            Some(ref mut vars) => vars.extend(variables),
-            None => vars = Some(variables),
-        }
-    }
+    let vars = Some(collect_variables(args).await?);
     debug!("vars: {:#?}", vars);
     let mut workspaces_variables = Vec::new();
     if let Some(workspace_name) = &args.default.workspace_name {
@@ -1,28 +1,44 @@
-use super::WorkspaceArgs;
+use super::about;
 use crate::{
-    cli::command::common::{check_workspace_identifier, parse_workspace_file},
+    cli::command::common::{
+        check_workspace_identifier, parse_workspace_file, WorkspaceArgs,
+    },
     error::ArgError,
     settings::Settings,
 };
 
-use log::info;
+use clap::Args;
+use log::{info, warn};
+use std::path::{Path, PathBuf};
 use surf::Client;
 use tfc_toolset::{
     settings::Core,
     variable,
-    workspace::{self, WorkspaceVariables},
+    workspace::{self, Workspace, WorkspaceVariables},
 };
-use tfc_toolset_extras::{parse_workspace_name, VariablesFile};
+use tfc_toolset_extras::{
+    parse_workspace_name, DotenvFile, TfvarsFile, VariablesFile,
+};
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    #[clap(flatten)]
+    pub default: WorkspaceArgs,
+    #[arg(long, help = about::EXPORT_TFVARS_FILE)]
+    pub tfvars_file: Option<PathBuf>,
+    #[arg(long, help = about::EXPORT_ENV_FILE)]
+    pub env_file: Option<PathBuf>,
+}
 
 pub async fn list(
-    args: &WorkspaceArgs,
+    args: &ListArgs,
     core: &Core,
     config: &Settings,
     client: Client,
 ) -> miette::Result<Vec<WorkspaceVariables>, ArgError> {
-    check_workspace_identifier(args)?;
+    check_workspace_identifier(&args.default)?;
     let mut workspaces_variables = Vec::new();
-    if let Some(workspace_name) = &args.workspace_name {
+    if let Some(workspace_name) = &args.default.workspace_name {
         parse_workspace_name(workspace_name)?;
         info!("Retrieving variables for workspace {}.", workspace_name);
         let workspace =
@@ -32,7 +48,7 @@ pub async fn list(
             variable::list(&workspace.id, core, client.clone()).await?;
         workspaces_variables =
             vec![WorkspaceVariables { workspace, variables }];
-    } else if let Some(workspace_id) = &args.workspace_id {
+    } else if let Some(workspace_id) = &args.default.workspace_id {
         info!("Retrieving variables for workspace {}.", workspace_id);
         let workspace =
             workspace::show(workspace_id, core, client.clone()).await?;
@@ -40,22 +56,96 @@ pub async fn list(
             variable::list(workspace_id, core, client.clone()).await?;
         workspaces_variables =
             vec![WorkspaceVariables { workspace, variables }];
-    } else if let Some(file_path) = &args.workspace_file {
+    } else if let Some(file_path) = &args.default.workspace_file {
         let workspaces =
             parse_workspace_file(file_path, core, client.clone()).await?;
         info!("Batch retrieving variables for workspaces: {:#?}", workspaces);
         workspaces_variables =
-            variable::list_batch(core, client.clone(), workspaces).await?;
-    } else if args.auto_discover_workspaces {
+            batch_variables(core, client.clone(), workspaces).await?;
+    } else if args.default.auto_discover_workspaces {
         let workspaces = workspace::list(true, core, client.clone()).await?;
         info!("Batch retrieving variables for workspaces: {:#?}", workspaces);
         workspaces_variables =
-            variable::list_batch(core, client.clone(), workspaces).await?;
+            batch_variables(core, client.clone(), workspaces).await?;
     }
     info!("{:#?}", &workspaces_variables);
     if core.save_output {
         VariablesFile::from(workspaces_variables.clone())
             .save(&core.output, config.pretty_output)?;
     }
+    export(args, &workspaces_variables)?;
     Ok(workspaces_variables)
 }
+
+// Report every workspace that failed before giving up, so one bad workspace
+// doesn't hide the others
+async fn batch_variables(
+    core: &Core,
+    client: Client,
+    workspaces: Vec<Workspace>,
+) -> Result<Vec<WorkspaceVariables>, ArgError> {
+    let batch = variable::list_batch(core, client, workspaces).await;
+    batch.log_failures();
+    Ok(batch.into_result()?)
+}
+
+// When exporting more than one workspace each file is prefixed with the
+// workspace name
+fn export_path(path: &Path, workspace_name: &str, prefix: bool) -> PathBuf {
+    if !prefix {
+        return path.to_path_buf();
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.{}", workspace_name, file_name))
+}
+
+// Terraform loads every *.auto.tfvars file in a directory, so exporting
+// more than one workspace to them would silently merge their variables
+const AUTO_TFVARS: &str = ".auto.tfvars";
+
+fn tfvars_export_path(
+    path: &Path,
+    workspace_name: &str,
+    prefix: bool,
+) -> PathBuf {
+    let path = export_path(path, workspace_name, prefix);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    match file_name.strip_suffix(AUTO_TFVARS) {
+        Some(stem) if prefix => path.with_file_name(format!("{}.tfvars", stem)),
+        _ => path,
+    }
+}
+
+fn export(
+    args: &ListArgs,
+    workspaces_variables: &[WorkspaceVariables],
+) -> Result<(), ArgError> {
+    let prefix = workspaces_variables.len() > 1;
+    if let Some(path) = &args.tfvars_file {
+        if prefix && path.to_string_lossy().ends_with(AUTO_TFVARS) {
+            warn!(
+                "Exporting more than one workspace, the files are saved as .tfvars instead of {} so Terraform doesn't load them all at once.",
+                AUTO_TFVARS
+            );
+        }
+    }
+    for entry in workspaces_variables {
+        let workspace_name = entry
+            .workspace
+            .attributes
+            .name
+            .clone()
+            .unwrap_or(entry.workspace.id.clone());
+        if let Some(path) = &args.tfvars_file {
+            let path = tfvars_export_path(path, &workspace_name, prefix);
+            TfvarsFile::from(entry.variables.clone()).save(&path)?;
+            info!("Exported terraform variables to {}", path.display());
+        }
+        if let Some(path) = &args.env_file {
+            let path = export_path(path, &workspace_name, prefix);
+            DotenvFile::from(entry.variables.clone()).save(&path)?;
+            info!("Exported environment variables to {}", path.display());
+        }
+    }
+    Ok(())
+}
@@ -1,20 +1,28 @@
 mod about;
+pub(crate) mod copy;
 pub(crate) mod create;
 pub(crate) mod delete;
 pub(crate) mod list;
+pub(crate) mod set;
+pub(crate) mod sync;
 
+pub use copy::copy;
 pub use create::create;
 pub use delete::delete;
-pub use list::list;
+pub use list::{list, ListArgs};
+pub use set::set;
 use std::str::FromStr;
+pub use sync::sync;
 
 use super::common::WorkspaceArgs;
 
 use crate::error::ArgError;
 use clap::{Args, Subcommand};
+use copy::CopyArgs;
 use delete::DeleteArgs;
+use sync::SyncArgs;
 use tfc_toolset::variable::Variable;
-use tfc_toolset_extras::VariablesFile;
+use tfc_toolset_extras::{DotenvFile, TfvarsFile, VariablesFile};
 
 #[derive(Args, Debug)]
 pub(crate) struct Commands {
@@ -29,7 +37,13 @@ pub(crate) enum VariableCmds {
     #[clap(about = about::DELETE)]
     Delete(DeleteArgs),
     #[clap(about = about::LIST)]
-    List(WorkspaceArgs),
+    List(ListArgs),
+    #[clap(about = about::SET)]
+    Set(ManageArgs),
+    #[clap(about = about::SYNC)]
+    Sync(SyncArgs),
+    #[clap(about = about::COPY)]
+    Copy(CopyArgs),
 }
 
 #[derive(Args, Debug)]
@@ -38,6 +52,10 @@ pub struct ManageArgs {
     pub var: Vec<String>,
     #[arg(long, help = about::VAR_FILE)]
     pub var_file: Option<String>,
+    #[arg(long, help = about::TFVARS_FILE)]
+    pub tfvars_file: Option<String>,
+    #[arg(long, help = about::ENV_FILE)]
+    pub env_file: Option<String>,
     #[clap(flatten)]
     default: WorkspaceArgs,
 }
@@ -45,7 +63,11 @@ pub struct ManageArgs {
 pub(crate) fn check_variable_identifier_basic(
     args: &ManageArgs,
 ) -> Result<(), ArgError> {
-    if args.var.is_empty() && args.var_file.is_none() {
+    if args.var.is_empty()
+        && args.var_file.is_none()
+        && args.tfvars_file.is_none()
+        && args.env_file.is_none()
+    {
         Err(ArgError::MissingVariableIdentifierBasic)
     } else {
         Ok(())
@@ -87,3 +109,24 @@ pub(crate) async fn parse_variable_file(
     }
     Ok(variables)
 }
+
+/// Gather the variables given by `--var`, `--var-file`, `--tfvars-file` and
+/// `--env-file`
+pub(crate) async fn collect_variables(
+    args: &ManageArgs,
+) -> Result<Vec<Variable>, ArgError> {
+    let mut variables = Vec::new();
+    for var_string in args.var.iter() {
+        variables.push(Variable::from_str(var_string)?);
+    }
+    if let Some(variables_file) = &args.var_file {
+        variables.extend(parse_variable_file(variables_file).await?);
+    }
+    if let Some(tfvars_file) = &args.tfvars_file {
+        variables.extend(TfvarsFile::load(tfvars_file)?.variables);
+    }
+    if let Some(env_file) = &args.env_file {
+        variables.extend(DotenvFile::load(env_file)?.variables);
+    }
+    Ok(variables)
+}
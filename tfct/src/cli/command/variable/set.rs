@@ -0,0 +1,43 @@
+use super::ManageArgs;
+use crate::{
+    cli::command::common::resolve_workspaces,
+    error::ArgError,
+    settings::Settings,
+    variable::{check_variable_identifier_basic, collect_variables},
+};
+
+use log::{debug, info};
+use surf::Client;
+use tfc_toolset::{settings::Core, variable, workspace::WorkspaceVariables};
+use tfc_toolset_extras::VariablesFile;
+
+pub async fn set(
+    args: &ManageArgs,
+    core: &Core,
+    config: &Settings,
+    client: Client,
+) -> miette::Result<Vec<WorkspaceVariables>, ArgError> {
+    check_variable_identifier_basic(args)?;
+    let vars = collect_variables(args).await?;
+    debug!("vars: {:#?}", vars);
+    let workspaces =
+        resolve_workspaces(&args.default, core, client.clone()).await?;
+    let mut workspaces_variables = Vec::new();
+    for workspace in workspaces {
+        info!("Setting variables on workspace {}.", workspace.id);
+        let variables = variable::upsert_all(
+            &workspace.id,
+            vars.clone(),
+            core,
+            client.clone(),
+        )
+        .await?;
+        workspaces_variables.push(WorkspaceVariables { workspace, variables });
+    }
+    info!("{:#?}", &workspaces_variables);
+    if core.save_output {
+        VariablesFile::from(workspaces_variables.clone())
+            .save(&core.output, config.pretty_output)?;
+    }
+    Ok(workspaces_variables)
+}
@@ -0,0 +1,147 @@
+use super::{about, parse_variable_file};
+use crate::{
+    cli::command::common::{resolve_workspaces, WorkspaceArgs},
+    error::ArgError,
+};
+
+use clap::Args;
+use log::info;
+use std::{
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+use surf::Client;
+use tfc_toolset::{
+    error::ToolError,
+    settings::Core,
+    variable::{self, Category, SyncPlan, Variable},
+    workspace::Workspace,
+};
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    #[arg(long, help = about::SYNC_VAR_FILE)]
+    pub var_file: String,
+    #[arg(
+        short,
+        long,
+        help = about::CATEGORY,
+        value_parser = Category::from_str,
+        required = false
+    )]
+    pub category: Vec<Category>,
+    #[arg(
+    long,
+    action,
+    help = about::AUTO_APPROVE,
+    default_value = "false",
+    required = false
+    )]
+    pub auto_approve: bool,
+    #[clap(flatten)]
+    default: WorkspaceArgs,
+}
+
+pub async fn sync(
+    args: &SyncArgs,
+    core: &Core,
+    client: Client,
+) -> miette::Result<(), ArgError> {
+    let desired = parse_variable_file(&args.var_file).await?;
+    let workspaces =
+        resolve_workspaces(&args.default, core, client.clone()).await?;
+    let mut plans: Vec<(Workspace, SyncPlan)> = Vec::new();
+    for workspace in workspaces {
+        let plan = variable::plan_sync(
+            &workspace.id,
+            &desired,
+            &args.category,
+            core,
+            client.clone(),
+        )
+        .await?;
+        print_plan(&workspace, &plan);
+        plans.push((workspace, plan));
+    }
+    if !plans.iter().any(|(_, plan)| plan.has_changes()) {
+        println!("No changes. Variables are up to date.");
+        return Ok(());
+    }
+    if !args.auto_approve && !confirm()? {
+        println!("Sync cancelled.");
+        return Ok(());
+    }
+    for (workspace, plan) in plans.iter().filter(|(_, p)| p.has_changes()) {
+        variable::sync(&workspace.id, plan, core, client.clone()).await?;
+    }
+    info!("Finished syncing variables.");
+    Ok(())
+}
+
+fn display_value(var: &Variable) -> String {
+    if var.attributes.sensitive.unwrap_or(false) {
+        "(sensitive)".to_string()
+    } else {
+        format!("{:?}", var.attributes.value.clone().unwrap_or_default())
+    }
+}
+
+fn print_plan(workspace: &Workspace, plan: &SyncPlan) {
+    let name =
+        workspace.attributes.name.clone().unwrap_or(workspace.id.clone());
+    println!("Workspace {}:", name);
+    for var in &plan.creates {
+        println!(
+            "  + {} ({}) = {}",
+            var.attributes.key,
+            var.attributes.category,
+            display_value(var)
+        );
+    }
+    for update in &plan.updates {
+        let value = if update.desired.attributes.value.is_some() {
+            format!(
+                "{} -> {}",
+                display_value(&update.current),
+                display_value(&update.desired)
+            )
+        } else {
+            display_value(&update.current)
+        };
+        println!(
+            "  ~ {} ({}) = {}",
+            update.current.attributes.key,
+            update.current.attributes.category,
+            value
+        );
+    }
+    for var in &plan.deletes {
+        println!("  - {} ({})", var.attributes.key, var.attributes.category);
+    }
+    for var in &plan.skipped {
+        println!(
+            "  ! {} ({}) is sensitive and has no value, skipped",
+            var.attributes.key, var.attributes.category
+        );
+    }
+    for conflict in &plan.conflicts {
+        println!(
+            "  ! {} ({}) is sensitive and can't be made non-sensitive, delete it to recreate it, skipped",
+            conflict.current.attributes.key, conflict.current.attributes.category
+        );
+    }
+    println!(
+        "  {} to create, {} to update, {} to delete.",
+        plan.creates.len(),
+        plan.updates.len(),
+        plan.deletes.len()
+    );
+}
+
+fn confirm() -> Result<bool, ToolError> {
+    print!("Do you want to apply these changes? Only 'yes' will be accepted: ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim() == "yes")
+}
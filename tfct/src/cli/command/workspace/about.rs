@@ -14,6 +14,25 @@ pub(super) const FILTER_LIST: &str =
 
 pub(super) const SHOW: &str = "Show details about a workspace";
 
+pub(super) const LOCK: &str = "Lock one or more workspaces";
+
+pub(super) const UNLOCK: &str = "Unlock one or more workspaces";
+
+pub(super) const LOCK_REASON: &str =
+    "The reason for locking the workspaces, shown in the UI";
+
+pub(super) const FORCE_UNLOCK: &str =
+    "Force unlock the workspaces even if they were locked by another user or run, requires admin access";
+
+pub(super) const OUTPUTS: &str =
+    "Show the outputs from the current state of one or more workspaces";
+
+pub(super) const OUTPUT_NAME: &str =
+    "The name of an output to show, all outputs are shown if omitted";
+
+pub(super) const SHOW_SENSITIVE: &str =
+    "Show the values of sensitive outputs instead of redacting them";
+
 pub(super) const NAME: &str = "The name of the workspace";
 
 pub(super) const AGENT_POOL_ID: &str =
@@ -22,8 +22,9 @@ pub async fn create(
     info!("Creating Workspace: {}", &args.name.clone().unwrap());
     let options = build_options(args)?;
     debug!("{:#?}", &options);
-    let workspace =
-        workspace::create(options, core, client).await.into_diagnostic()?;
+    let workspace = workspace::create(options, core, client)
+        .await
+        .map_err(ArgError::from)?;
     info!("{:#?}", &workspace);
     if core.save_output {
         WorkspacesFile::from(vec![workspace.clone()])
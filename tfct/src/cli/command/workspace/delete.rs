@@ -1,6 +1,9 @@
 use super::about;
-use crate::cli::command::common::{
-    check_workspace_identifier_basic, WorkspaceArgsBasic,
+use crate::{
+    cli::command::common::{
+        check_workspace_identifier_basic, WorkspaceArgsBasic,
+    },
+    error::ArgError,
 };
 
 use clap::Args;
@@ -36,12 +39,12 @@ pub async fn delete(
         info!("Deleting Workspace: {}", workspace_name);
         workspace::delete_by_name(workspace_name, args.safe, config, client)
             .await
-            .into_diagnostic()?;
+            .map_err(ArgError::from)?;
     } else if let Some(workspace_id) = &args.workspace.workspace_id {
         info!("Deleting Workspace: {}", workspace_id);
         workspace::delete(workspace_id, args.safe, config, client)
             .await
-            .into_diagnostic()?;
+            .map_err(ArgError::from)?;
     }
     Ok(())
 }
@@ -1,5 +1,5 @@
 use super::about;
-use crate::settings::Settings;
+use crate::{error::ArgError, settings::Settings};
 
 use clap::Args;
 use log::info;
@@ -31,8 +31,9 @@ pub async fn list(
     client: Client,
 ) -> miette::Result<Vec<Workspace>> {
     info!("Retrieving Workspaces.");
-    let workspaces =
-        workspace::list(args.filter, core, client).await.into_diagnostic()?;
+    let workspaces = workspace::list(args.filter, core, client)
+        .await
+        .map_err(ArgError::from)?;
     info!("{:#?}", &workspaces);
     if core.save_output {
         WorkspacesFile::from(workspaces.clone())
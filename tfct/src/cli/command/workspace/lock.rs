@@ -0,0 +1,64 @@
+use super::about;
+use crate::{
+    cli::command::common::{resolve_workspaces, WorkspaceArgs},
+    error::ArgError,
+};
+
+use clap::Args;
+use log::{error, info};
+use surf::Client;
+use tfc_toolset::{settings::Core, workspace};
+
+#[derive(Args, Debug)]
+pub struct LockArgs {
+    #[clap(flatten)]
+    pub workspace: WorkspaceArgs,
+    #[arg(short, long, help = about::LOCK_REASON)]
+    pub reason: Option<String>,
+}
+
+pub async fn lock(
+    args: &LockArgs,
+    config: &Core,
+    client: Client,
+) -> miette::Result<(), ArgError> {
+    let workspaces =
+        resolve_workspaces(&args.workspace, config, client.clone()).await?;
+    let total = workspaces.len();
+    let mut locked = vec![];
+    let mut already_locked = vec![];
+    let mut failed = 0;
+    for ws in workspaces {
+        let name = ws.attributes.name.clone().unwrap_or(ws.id.clone());
+        match workspace::lock(
+            &ws.id,
+            args.reason.clone(),
+            config,
+            client.clone(),
+        )
+        .await
+        {
+            Ok(_) => locked.push(name),
+            // The API is the source of truth for the lock, the workspace may
+            // have come from a stale file
+            Err(e) if e.is_conflict() => already_locked.push(name),
+            Err(e) => {
+                error!("Failed to lock workspace {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    info!("Locked workspaces: {:?}", locked);
+    if !already_locked.is_empty() {
+        info!("Workspaces that were already locked: {:?}", already_locked);
+    }
+    if failed > 0 {
+        return Err(ArgError::PartialFailure {
+            action: "lock",
+            items: "workspaces",
+            failed,
+            total,
+        });
+    }
+    Ok(())
+}
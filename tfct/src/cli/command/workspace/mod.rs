@@ -2,8 +2,11 @@ mod about;
 pub(crate) mod create;
 pub(crate) mod delete;
 pub(crate) mod list;
+pub(crate) mod lock;
+pub(crate) mod outputs;
 
 pub(crate) mod show;
+pub(crate) mod unlock;
 pub(crate) mod update;
 
 use crate::error::ArgError;
@@ -11,7 +14,10 @@ use crate::error::ArgError;
 pub use create::create;
 pub use delete::{delete, DeleteArgs};
 pub use list::{list, ListArgs};
+pub use lock::{lock, LockArgs};
+pub use outputs::{outputs, OutputsArgs};
 pub use show::show;
+pub use unlock::{unlock, UnlockArgs};
 pub use update::{update, UpdateArgs};
 
 use crate::cli::command::common::WorkspaceArgsBasic;
@@ -38,6 +44,12 @@ pub(crate) enum WorkspaceCmds {
     List(ListArgs),
     #[clap(about = about::SHOW)]
     Show(WorkspaceArgsBasic),
+    #[clap(about = about::LOCK)]
+    Lock(LockArgs),
+    #[clap(about = about::UNLOCK)]
+    Unlock(UnlockArgs),
+    #[clap(about = about::OUTPUTS)]
+    Outputs(OutputsArgs),
 }
 
 #[derive(Args, Debug)]
@@ -0,0 +1,100 @@
+use super::about;
+use crate::{
+    cli::command::common::{resolve_workspaces, WorkspaceArgs},
+    error::ArgError,
+    settings::Settings,
+};
+
+use clap::Args;
+use log::{info, warn};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs::File};
+use surf::Client;
+use tfc_toolset::{error::ToolError, settings::Core, state_version};
+
+const REDACTED: &str = "<sensitive>";
+
+#[derive(Args, Debug)]
+pub struct OutputsArgs {
+    #[clap(flatten)]
+    pub workspace: WorkspaceArgs,
+    #[arg(short, long, help = about::OUTPUT_NAME, required = false)]
+    pub name: Vec<String>,
+    #[arg(
+    long,
+    action,
+    help = about::SHOW_SENSITIVE,
+    default_value = "false",
+    required = false
+    )]
+    pub show_sensitive: bool,
+}
+
+pub type WorkspaceOutputs = BTreeMap<String, BTreeMap<String, Value>>;
+
+pub async fn outputs(
+    args: &OutputsArgs,
+    core: &Core,
+    config: &Settings,
+    client: Client,
+) -> miette::Result<WorkspaceOutputs, ArgError> {
+    let workspaces =
+        resolve_workspaces(&args.workspace, core, client.clone()).await?;
+    let mut workspace_outputs = WorkspaceOutputs::new();
+    for ws in workspaces {
+        let name = ws.attributes.name.clone().unwrap_or(ws.id.clone());
+        let state_version =
+            match state_version::current(&ws.id, core, client.clone()).await {
+                Ok(state_version) => state_version,
+                Err(e) if e.is_not_found() => {
+                    warn!("Workspace {} does not have any state.", name);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+        let mut values = BTreeMap::new();
+        for output in
+            state_version::outputs(&state_version.id, core, client.clone())
+                .await?
+        {
+            if !args.name.is_empty()
+                && !args.name.contains(&output.attributes.name)
+            {
+                continue;
+            }
+            let attributes = if !output.attributes.sensitive {
+                output.attributes
+            } else if args.show_sensitive {
+                // The list of outputs leaves out sensitive values
+                state_version::output(&output.id, core, client.clone())
+                    .await?
+                    .attributes
+            } else {
+                let mut attributes = output.attributes;
+                attributes.value = Some(Value::String(REDACTED.to_string()));
+                attributes
+            };
+            values
+                .insert(attributes.name, attributes.value.unwrap_or_default());
+        }
+        workspace_outputs.insert(name, values);
+    }
+    let json = if config.pretty_output {
+        serde_json::to_string_pretty(&workspace_outputs)
+    } else {
+        serde_json::to_string(&workspace_outputs)
+    }
+    .map_err(ToolError::Json)?;
+    println!("{}", json);
+    if core.save_output {
+        let file = File::create(&core.output).map_err(ToolError::Io)?;
+        if config.pretty_output {
+            serde_json::to_writer_pretty(file, &workspace_outputs)
+        } else {
+            serde_json::to_writer(file, &workspace_outputs)
+        }
+        .map_err(ToolError::Json)?;
+        info!("Outputs saved to: {}", core.output.display());
+    }
+    Ok(workspace_outputs)
+}
@@ -2,6 +2,7 @@ use crate::{
     cli::command::common::{
         check_workspace_identifier_basic, WorkspaceArgsBasic,
     },
+    error::ArgError,
     settings::Settings,
 };
 use log::info;
@@ -21,7 +22,7 @@ pub async fn show(
         info!("Retrieving Workspace: {}", workspace_name);
         let workspace = workspace::show_by_name(workspace_name, core, client)
             .await
-            .into_diagnostic()?;
+            .map_err(ArgError::from)?;
         info!("{:#?}", &workspace);
         if core.save_output {
             WorkspacesFile::from(vec![workspace.clone()])
@@ -33,7 +34,7 @@ pub async fn show(
         info!("Retrieving Workspace: {}", workspace_id);
         let workspace = workspace::show(workspace_id, core, client)
             .await
-            .into_diagnostic()?;
+            .map_err(ArgError::from)?;
         info!("{:#?}", &workspace);
         if core.save_output {
             WorkspacesFile::from(vec![workspace.clone()])
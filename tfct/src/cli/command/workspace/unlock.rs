@@ -0,0 +1,71 @@
+use super::about;
+use crate::{
+    cli::command::common::{resolve_workspaces, WorkspaceArgs},
+    error::ArgError,
+};
+
+use clap::Args;
+use log::{error, info};
+use surf::Client;
+use tfc_toolset::{settings::Core, workspace};
+
+#[derive(Args, Debug)]
+pub struct UnlockArgs {
+    #[clap(flatten)]
+    pub workspace: WorkspaceArgs,
+    #[arg(
+    long,
+    action,
+    help = about::FORCE_UNLOCK,
+    default_value = "false",
+    required = false
+    )]
+    pub force: bool,
+}
+
+pub async fn unlock(
+    args: &UnlockArgs,
+    config: &Core,
+    client: Client,
+) -> miette::Result<(), ArgError> {
+    let workspaces =
+        resolve_workspaces(&args.workspace, config, client.clone()).await?;
+    let total = workspaces.len();
+    let mut unlocked = vec![];
+    let mut not_locked = vec![];
+    let mut failed = 0;
+    for ws in workspaces {
+        let name = ws.attributes.name.clone().unwrap_or(ws.id.clone());
+        let result = if args.force {
+            workspace::force_unlock(&ws.id, config, client.clone()).await
+        } else {
+            workspace::unlock(&ws.id, config, client.clone()).await
+        };
+        match result {
+            Ok(_) => unlocked.push(name),
+            // The API is the source of truth for the lock, the workspace may
+            // have come from a stale file
+            Err(e) if e.is_conflict() => not_locked.push(name),
+            Err(e) => {
+                error!("Failed to unlock workspace {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    info!("Unlocked workspaces: {:?}", unlocked);
+    if !not_locked.is_empty() {
+        info!(
+            "Workspaces that were not locked, or were locked by someone else: {:?}",
+            not_locked
+        );
+    }
+    if failed > 0 {
+        return Err(ArgError::PartialFailure {
+            action: "unlock",
+            items: "workspaces",
+            failed,
+            total,
+        });
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ use crate::{
     cli::command::common::{
         check_workspace_identifier_basic, WorkspaceArgsBasic,
     },
+    error::ArgError,
     settings::Settings,
 };
 
@@ -38,7 +39,7 @@ pub async fn update(
         let workspace =
             workspace::update_by_name(workspace_name, options, core, client)
                 .await
-                .into_diagnostic()?;
+                .map_err(ArgError::from)?;
         info!("{:#?}", &workspace);
         if core.save_output {
             WorkspacesFile::from(vec![workspace.clone()])
@@ -50,7 +51,7 @@ pub async fn update(
         info!("Updating Workspace: {}", workspace_id);
         let workspace = workspace::update(workspace_id, options, core, client)
             .await
-            .into_diagnostic()?;
+            .map_err(ArgError::from)?;
         info!("{:#?}", &workspace);
         if core.save_output {
             WorkspacesFile::from(vec![workspace.clone()])
@@ -5,11 +5,16 @@ use crate::{
     settings::Settings,
 };
 use clap::{Args, Parser, Subcommand};
-pub(super) use command::{clean, run, tag, variable, variable_set, workspace};
+pub(super) use command::{
+    cache, clean, config, run, tag, variable, variable_set, workspace,
+};
 use log::warn;
 use miette::IntoDiagnostic;
 use std::{path::PathBuf, str::FromStr};
-use tfc_toolset::settings::{Core, Query, Tag, Variable};
+use tfc_toolset::{
+    filter::expr::Expr,
+    settings::{Attribute, CacheMode, Core, LoadOptions, Query, Tag, Variable},
+};
 
 const CLI: &str =
     "A tool to help manage a toolset that helps manage your deployments";
@@ -20,7 +25,13 @@ const VARIABLE_SET: &str = "Manage variable sets";
 const TAG: &str = "Manage workspace tags";
 const RUN: &str = "Manage runs";
 const CLEAN: &str = "Run cleanup operations";
+const CACHE: &str = "Manage the HTTP cache";
+const CONFIG: &str = "Manage the configuration";
+const CONFIG_PATH: &str = "A settings file that takes precedence over every other settings file, can also be set with TFCT_CONFIG";
+const PROFILE: &str =
+    "The settings profile to use, can also be set with TFCT_PROFILE";
 const ORG: &str = "The name of the organization";
+const BASE_URL: &str = "The base URL of the Terraform Cloud or Enterprise API";
 const TOKEN: &str = "The token to use for authentication";
 const PROJECT: &str = "The id of the project";
 const LOG: &str = "The log level to use";
@@ -37,6 +48,17 @@ const QUERY_VARIABLE: &str =
     "The name of the variable to search for, formatted as key:operator:value";
 const QUERY_TAG: &str =
     "The name of the tag to search for, formatted as operator:name";
+const QUERY_ATTRIBUTE: &str =
+    "The workspace attribute to search for, formatted as attribute:operator:value";
+const INCLUDE: &str =
+    "Related resources to include when retrieving workspaces, e.g. vars";
+const QUERY: &str = "A boolean query expression to filter workspaces with";
+const CACHE_MODE: &str = "How the HTTP cache is used: default, no-store, reload, no-cache, force-cache or only-if-cached";
+const CACHE_TTL_HEURISTIC: &str =
+    "The fraction of the time since a response was last modified to treat it as fresh for";
+const CACHE_DIR: &str = "The directory to store cached responses in";
+const STATS: &str =
+    "Print the number of requests sent, retried and time spent throttled";
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = Some(CLI))]
@@ -62,12 +84,22 @@ pub(super) enum Commands {
     Run(Box<run::Commands>),
     #[clap(about = CLEAN)]
     Clean(Box<clean::Commands>),
+    #[clap(about = CACHE)]
+    Cache(Box<cache::Commands>),
+    #[clap(about = CONFIG)]
+    Config(Box<config::Commands>),
 }
 
 #[derive(Args, Debug)]
 pub struct RootArgs {
+    #[arg(long, help = CONFIG_PATH, global = true)]
+    pub config: Option<PathBuf>,
+    #[arg(long, help = PROFILE, global = true)]
+    pub profile: Option<String>,
     #[arg(long, help = ORG, global = true)]
     pub org: Option<String>,
+    #[arg(long, help = BASE_URL, global = true)]
+    pub base_url: Option<String>,
     #[arg(long, help = TOKEN, global = true)]
     pub token: Option<String>,
     #[arg(long, help = PROJECT, global = true)]
@@ -94,6 +126,24 @@ pub struct RootArgs {
     pub query_variable: Option<Vec<String>>,
     #[arg(long, help = QUERY_TAG, global = true)]
     pub query_tag: Option<Vec<String>>,
+    #[arg(long, help = QUERY_ATTRIBUTE, global = true)]
+    pub query_attribute: Option<Vec<String>>,
+    #[arg(long, help = QUERY, global = true)]
+    pub query: Option<String>,
+    #[arg(long, help = INCLUDE, global = true, value_delimiter = ',')]
+    pub include: Option<Vec<String>>,
+    #[arg(long, help = CACHE_MODE, global = true)]
+    pub cache_mode: Option<String>,
+    #[arg(long, help = CACHE_TTL_HEURISTIC, global = true)]
+    pub cache_ttl_heuristic: Option<f32>,
+    #[arg(long, help = CACHE_DIR, global = true)]
+    pub cache_dir: Option<PathBuf>,
+    #[arg(action, long, help = STATS, global = true, default_value = "false")]
+    pub stats: bool,
+}
+
+pub(crate) fn load_options(args: &RootArgs) -> LoadOptions {
+    LoadOptions { config: args.config.clone(), profile: args.profile.clone() }
 }
 
 pub(crate) fn override_core(
@@ -103,6 +153,9 @@ pub(crate) fn override_core(
     if let Some(org) = &args.org {
         config.org = org.clone();
     }
+    if let Some(base_url) = &args.base_url {
+        config.set_base_url(base_url);
+    }
     if let Some(token) = &args.token {
         config.token = token.clone();
     }
@@ -133,6 +186,8 @@ pub(crate) fn override_core(
                     wildcard_name: None,
                     variables: None,
                     tags: None,
+                    attributes: None,
+                    expr: None,
                 })
             }
         }
@@ -148,6 +203,8 @@ pub(crate) fn override_core(
                     wildcard_name: Some(query_wildcard_name.clone()),
                     variables: None,
                     tags: None,
+                    attributes: None,
+                    expr: None,
                 })
             }
         }
@@ -175,6 +232,8 @@ pub(crate) fn override_core(
                     wildcard_name: None,
                     variables: Some(variables),
                     tags: None,
+                    attributes: None,
+                    expr: None,
                 })
             }
         }
@@ -200,10 +259,61 @@ pub(crate) fn override_core(
                     wildcard_name: None,
                     variables: None,
                     tags: Some(tags),
+                    attributes: None,
+                    expr: None,
+                })
+            }
+        }
+    }
+    if let Some(query_attribute) = &args.query_attribute {
+        let mut attributes = vec![];
+        for attribute in query_attribute {
+            let entry = Attribute::from_str(attribute).into_diagnostic()?;
+            attributes.push(entry);
+        }
+        match &mut config.workspaces.query {
+            Some(query) => query.attributes = Some(attributes),
+            None => {
+                config.workspaces.query = Some(Query {
+                    name: None,
+                    wildcard_name: None,
+                    variables: None,
+                    tags: None,
+                    attributes: Some(attributes),
+                    expr: None,
+                })
+            }
+        }
+    }
+    if let Some(query_expr) = &args.query {
+        let expr = Expr::from_str(query_expr).into_diagnostic()?;
+        match &mut config.workspaces.query {
+            Some(query) => query.expr = Some(expr),
+            None => {
+                config.workspaces.query = Some(Query {
+                    name: None,
+                    wildcard_name: None,
+                    variables: None,
+                    tags: None,
+                    attributes: None,
+                    expr: Some(expr),
                 })
             }
         }
     }
+    if let Some(include) = &args.include {
+        config.workspaces.include = Some(include.clone());
+    }
+    if let Some(cache_mode) = &args.cache_mode {
+        config.cache.mode =
+            CacheMode::from_str(cache_mode).into_diagnostic()?;
+    }
+    if let Some(cache_ttl_heuristic) = args.cache_ttl_heuristic {
+        config.cache.ttl_heuristic = cache_ttl_heuristic;
+    }
+    if let Some(cache_dir) = &args.cache_dir {
+        config.cache.dir = Some(cache_dir.clone());
+    }
     config.save_output = args.save_output;
     Ok(())
 }
@@ -1,4 +1,5 @@
 use miette::Diagnostic;
+use tfc_toolset::error::{ApiError, ToolError};
 use thiserror::Error;
 
 /// An error type for argument parsing
@@ -9,7 +10,7 @@ pub enum ArgError {
     #[diagnostic(
         code(tfct::auth::missing_token),
         help(
-            "Must provide a token via `--token` argument or in settings.toml"
+            "Must provide a token via `--token` argument, `token` or `token_command` in settings.toml, a `TF_TOKEN_<host>` environment variable or `terraform login`"
         )
     )]
     MissingToken,
@@ -45,7 +46,7 @@ pub enum ArgError {
     #[error("Missing variable identifier")]
     #[diagnostic(
         code(tfct::tag::missing_variable_identifier),
-        help("Must provide either `--var` or `--var-file`")
+        help("Must provide either `--var`, `--var-file`, `--tfvars-file` or `--env-file`")
     )]
     MissingVariableIdentifierBasic,
     /// Missing tag identifier
@@ -69,16 +70,112 @@ pub enum ArgError {
         help("Must provide either `--workspace-name` (-w) or `--workspace-id` (-i)")
     )]
     MissingWorkspaceIdentifierBasic,
+    /// Missing source workspace
+    #[error("Missing source workspace")]
+    #[diagnostic(
+        code(tfct::variable::missing_source_workspace),
+        help(
+            "Must provide either `--from-workspace` or `--from-workspace-id`"
+        )
+    )]
+    MissingSourceWorkspace,
+    /// Invalid key pattern
+    #[error("Invalid key pattern")]
+    #[diagnostic(
+        code(tfct::variable::invalid_pattern),
+        help("Patterns must be valid regular expressions, see https://docs.rs/regex/latest/regex/#syntax")
+    )]
+    InvalidPattern(#[from] regex::Error),
+    /// Missing run identifier
+    #[error("Missing run identifier")]
+    #[diagnostic(
+        code(tfct::run::missing_run_identifier),
+        help("Must provide either `--run-id` (-i) or `--results-file` (-r)")
+    )]
+    MissingRunIdentifier,
+    /// Some of the items in a batch failed
+    #[error("Could not {action} {failed} of {total} {items}")]
+    #[diagnostic(
+        code(tfct::partial_failure),
+        help("See the errors logged above for each failure")
+    )]
+    PartialFailure {
+        action: &'static str,
+        items: &'static str,
+        failed: usize,
+        total: usize,
+    },
+    /// Unsuccessful response from the Terraform Cloud API
+    #[error("Request failed with status {status}")]
+    #[diagnostic(code(tfct::tfc_toolset::api_error))]
+    Api {
+        status: u16,
+        #[help]
+        help: Option<String>,
+        #[related]
+        errors: Vec<ApiErrorDetail>,
+    },
+    /// Settings file already exists
+    #[error("Settings file {0} already exists")]
+    #[diagnostic(
+        code(tfct::config::settings_file_exists),
+        help("Pass `--force` to overwrite it or `--path` to write somewhere else")
+    )]
+    SettingsFileExists(String),
+    /// Unable to print the configuration
+    #[error("Unable to serialize configuration")]
+    #[diagnostic(code(tfct::config::serialize))]
+    Toml(#[from] toml::ser::Error),
     /// Errors from tfc-toolset
     #[error(transparent)]
     #[diagnostic(code(tfct::tfc_toolset::tool_error))]
-    ToolSetError(#[from] tfc_toolset::error::ToolError),
+    ToolSetError(tfc_toolset::error::ToolError),
     /// Errors from tfc-toolset-extras
     #[error(transparent)]
     #[diagnostic(code(tfct::tfc_toolset_extras::extras_error))]
     ExtrasError(#[from] tfc_toolset_extras::error::ExtrasError),
 }
 
+impl From<ToolError> for ArgError {
+    fn from(e: ToolError) -> Self {
+        match e {
+            ToolError::Api { status, errors } => ArgError::Api {
+                status,
+                help: api_help(status),
+                errors: errors.into_iter().map(ApiErrorDetail::from).collect(),
+            },
+            e => ArgError::ToolSetError(e),
+        }
+    }
+}
+
+fn api_help(status: u16) -> Option<String> {
+    let help = match status {
+        401 => "Check that the token is valid and has not expired",
+        403 => "The token does not have permission to perform this action",
+        404 => "Check that the organization and resource exist, and that the token has access to them",
+        409 => "The resource is in a conflicting state, such as a locked workspace or an active run",
+        422 => "The request was rejected, see the details below",
+        429 => "Too many requests, try again later or lower the request rate",
+        _ => return None,
+    };
+    Some(help.to_string())
+}
+
+/// A single error returned by the Terraform Cloud API
+#[derive(Error, Diagnostic, Debug)]
+#[error("{message}")]
+#[diagnostic(code(tfct::tfc_toolset::api_error_detail))]
+pub struct ApiErrorDetail {
+    message: String,
+}
+
+impl From<ApiError> for ApiErrorDetail {
+    fn from(e: ApiError) -> Self {
+        Self { message: e.to_string() }
+    }
+}
+
 /// An error type for clean operations
 #[derive(Error, Diagnostic, Debug)]
 pub enum CleanError {
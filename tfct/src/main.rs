@@ -5,8 +5,10 @@ mod settings;
 
 use clap::Parser;
 use cli::{
+    cache::{self, CacheCmds},
     clean::{self, CleanCmds},
-    override_clean_config, override_config, override_core,
+    config::{self, ConfigCmds},
+    load_options, override_clean_config, override_config, override_core,
     run::{self, RunCmds},
     tag::{self, TagCmds},
     validate_core,
@@ -18,103 +20,170 @@ use cli::{
 use env_logger::Env;
 use miette::{IntoDiagnostic, WrapErr};
 use settings::Settings;
+use surf::Client;
 use tfc_toolset::{error::SETTINGS_ERROR, settings::Core};
-use tfc_toolset_extras::default_client;
+use tfc_toolset_extras::build_client;
 
 #[async_std::main]
 async fn main() -> miette::Result<()> {
     // Parse cli subcommands and arguments
     let cli = Cli::parse();
     // Get the settings for the run
-    let mut core = Core::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
-    let mut config =
-        Settings::new().into_diagnostic().wrap_err(SETTINGS_ERROR)?;
+    let options = load_options(&cli.root);
+    let mut core = Core::with_options(&options)
+        .into_diagnostic()
+        .wrap_err(SETTINGS_ERROR)?;
+    let mut config = Settings::with_options(&options)
+        .into_diagnostic()
+        .wrap_err(SETTINGS_ERROR)?;
     // Override the configs with any cli arguments
     override_core(&mut core, &cli.root)?;
     override_config(&mut config, &cli.root);
     // Initialize the logger
     env_logger::Builder::from_env(Env::default().default_filter_or(&core.log))
         .init();
-    validate_core(&core)?;
-    let client = default_client(None).into_diagnostic()?;
+    // Managing the cache does not talk to the API, so no token is needed,
+    // and the config commands should work before one has been set up, or
+    // while the token command is broken
+    match &cli.command {
+        Commands::Cache(_) | Commands::Config(_) => {}
+        _ => {
+            core.resolve_token().into_diagnostic()?;
+            validate_core(&core)?;
+        }
+    }
+    let (client, stats) = build_client(&core).into_diagnostic()?;
+    let result = run(&cli, &core, &mut config, client).await;
+    if cli.root.stats {
+        eprintln!("{}", stats);
+    }
+    result
+}
+
+async fn run(
+    cli: &Cli,
+    core: &Core,
+    config: &mut Settings,
+    client: Client,
+) -> miette::Result<()> {
     // Match on the cli subcommand
     match &cli.command {
         Commands::Workspace(workspace_cmd) => match &workspace_cmd.command {
             WorkspaceCmds::Create(args) => {
-                workspace::create(args, &core, &config, client.clone()).await?;
+                workspace::create(args, core, config, client.clone()).await?;
             }
             WorkspaceCmds::Update(args) => {
-                workspace::update(args, &core, &config, client.clone()).await?;
+                workspace::update(args, core, config, client.clone()).await?;
             }
             WorkspaceCmds::Delete(args) => {
-                workspace::delete(args, &core, client.clone()).await?;
+                workspace::delete(args, core, client.clone()).await?;
             }
             WorkspaceCmds::List(args) => {
-                workspace::list(args, &core, &config, client.clone()).await?;
+                workspace::list(args, core, config, client.clone()).await?;
             }
             WorkspaceCmds::Show(args) => {
-                workspace::show(args, &core, &config, client.clone()).await?;
+                workspace::show(args, core, config, client.clone()).await?;
+            }
+            WorkspaceCmds::Lock(args) => {
+                workspace::lock(args, core, client.clone()).await?;
+            }
+            WorkspaceCmds::Unlock(args) => {
+                workspace::unlock(args, core, client.clone()).await?;
+            }
+            WorkspaceCmds::Outputs(args) => {
+                workspace::outputs(args, core, config, client.clone()).await?;
             }
         },
         Commands::Tag(tag_cmd) => match &tag_cmd.command {
             TagCmds::Add(args) => {
-                tag::add(args, &core, client.clone()).await?;
+                tag::add(args, core, client.clone()).await?;
             }
             TagCmds::List(args) => {
-                tag::list(args, &core, &config, client.clone()).await?;
+                tag::list(args, core, config, client.clone()).await?;
             }
             TagCmds::Remove(args) => {
-                tag::remove(args, &core, client.clone()).await?;
+                tag::remove(args, core, client.clone()).await?;
             }
         },
         Commands::Variable(variable_cmd) => match &variable_cmd.command {
             VariableCmds::Create(args) => {
-                variable::create(args, &core, &config, client.clone()).await?;
+                variable::create(args, core, config, client.clone()).await?;
             }
             VariableCmds::Delete(args) => {
-                variable::delete(args, &core, client.clone()).await?;
+                variable::delete(args, core, client.clone()).await?;
             }
             VariableCmds::List(args) => {
-                variable::list(args, &core, &config, client.clone()).await?;
+                variable::list(args, core, config, client.clone()).await?;
+            }
+            VariableCmds::Set(args) => {
+                variable::set(args, core, config, client.clone()).await?;
+            }
+            VariableCmds::Sync(args) => {
+                variable::sync(args, core, client.clone()).await?;
+            }
+            VariableCmds::Copy(args) => {
+                variable::copy(args, core, config, client.clone()).await?;
             }
         },
         Commands::VariableSet(variable_set_cmd) => {
             match &variable_set_cmd.command {
                 VariableSetCmds::Apply(args) => {
-                    variable_set::apply(args, &core, client.clone()).await?;
+                    variable_set::apply(args, core, client.clone()).await?;
                 }
                 VariableSetCmds::Remove(args) => {
-                    variable_set::remove(args, &core, client.clone()).await?;
+                    variable_set::remove(args, core, client.clone()).await?;
                 }
             }
         }
         Commands::Run(run_cmd) => match &run_cmd.command {
             RunCmds::Status(args) => {
-                run::status(args, &core, client.clone()).await?;
+                run::status(args, core, client.clone()).await?;
             }
             RunCmds::Spec(args) => {
-                let config = Settings::new()
+                let config = Settings::with_options(&load_options(&cli.root))
                     .into_diagnostic()
                     .wrap_err(SETTINGS_ERROR)?;
-                run::spec(args, &config, &core, client.clone()).await?;
+                run::spec(args, &config, core, client.clone()).await?;
             }
             RunCmds::Plan(args) => {
-                let config = Settings::new()
+                let config = Settings::with_options(&load_options(&cli.root))
                     .into_diagnostic()
                     .wrap_err(SETTINGS_ERROR)?;
-                run::plan(args, &config, &core, client.clone()).await?;
+                run::plan(args, &config, core, client.clone()).await?;
+            }
+            RunCmds::Apply(args) => {
+                run::apply(args, core, client.clone()).await?;
             }
             RunCmds::Cancel(args) => {
-                run::cancel(args, &core, client.clone()).await?;
+                run::cancel(args, core, client.clone()).await?;
             }
             RunCmds::Discard(args) => {
-                run::discard(args, &core, client.clone()).await?;
+                run::discard(args, core, client.clone()).await?;
+            }
+            RunCmds::Logs(args) => {
+                run::logs(args, core, client.clone()).await?;
             }
         },
         Commands::Clean(clean_cmd) => match &clean_cmd.command {
             CleanCmds::Workspace(args) => {
-                override_clean_config(&mut config, args);
-                clean::workspace(args, &config, &core, client.clone()).await?;
+                override_clean_config(config, args);
+                clean::workspace(args, config, core, client.clone()).await?;
+            }
+        },
+        Commands::Config(config_cmd) => match &config_cmd.command {
+            ConfigCmds::Show => {
+                config::show(core, config)?;
+            }
+            ConfigCmds::Init(args) => {
+                config::init(args)?;
+            }
+        },
+        Commands::Cache(cache_cmd) => match &cache_cmd.command {
+            CacheCmds::Info => {
+                cache::info(core)?;
+            }
+            CacheCmds::Clear => {
+                cache::clear(core).await?;
             }
         },
     }
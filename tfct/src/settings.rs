@@ -1,32 +1,34 @@
-use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+use config::{Config, ConfigError};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tfc_toolset::settings::{self, LoadOptions};
 
 pub const MAX_CONCURRENT_DEFAULT: u16 = 10;
 pub const MAX_ITERATIONS_DEFAULT: u16 = 10;
 pub const STATUS_CHECK_SLEEP_SECONDS_DEFAULT: u64 = 5;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Settings {
     pub run: Run,
     pub pretty_output: bool,
     pub cleanup: Cleanup,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Run {
     pub max_concurrent: Option<usize>,
     pub max_iterations: Option<usize>,
     pub status_check_sleep_seconds: Option<u64>,
     pub cancel_on_timeout: Option<bool>,
+    pub summarize_changes: Option<bool>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Repositories {
     pub git_dir: PathBuf,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Cleanup {
     pub dry_run: bool,
     pub unlisted_variables: bool,
@@ -36,12 +38,17 @@ pub struct Cleanup {
 
 impl Settings {
     pub fn new() -> Result<Self, ConfigError> {
-        let s = Config::builder()
+        Self::with_options(&LoadOptions::default())
+    }
+
+    pub fn with_options(options: &LoadOptions) -> Result<Self, ConfigError> {
+        let defaults = Config::builder()
             // Set defaults
             .set_default("pretty_output", false)?
             .set_default("run.max_concurrent", MAX_CONCURRENT_DEFAULT)?
             .set_default("run.max_iterations", MAX_ITERATIONS_DEFAULT)?
             .set_default("run.cancel_on_timeout", false)?
+            .set_default("run.summarize_changes", false)?
             .set_default(
                 "run.status_check_sleep_seconds",
                 STATUS_CHECK_SLEEP_SECONDS_DEFAULT,
@@ -55,13 +62,7 @@ impl Settings {
                     .unwrap_or("./".into())
                     .join("tfc-toolset")
                     .to_str(),
-            )?
-            // Start off by merging in the "default" configuration file
-            .add_source(File::with_name("settings.toml").required(false))
-            // Add in settings from the environment
-            // Eg.. `DEBUG=1 ./target/app` would set the `debug` key
-            .add_source(Environment::default())
-            .build()?;
-        s.try_deserialize()
+            )?;
+        settings::load(defaults, options)?.try_deserialize()
     }
 }